@@ -1,7 +1,7 @@
 use sp_core::{Pair, Public, sr25519};
 use node_template_runtime::{
 	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig,
-	SudoConfig, SystemConfig, WASM_BINARY, Signature, NodeAuthorizationConfig
+	SudoConfig, SystemConfig, WASM_BINARY, Signature, NodeAuthorizationConfig, Runtime,
 };
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_finality_grandpa::AuthorityId as GrandpaId;
@@ -9,6 +9,7 @@ use sp_runtime::traits::{Verify, IdentifyAccount};
 use sc_service::ChainType;
 
 use node_template_runtime::ContractsConfig;
+use pallet_contracts::{Schedule, SchedulePreset};
 
 use sp_core::OpaquePeerId; // A struct wraps Vec<u8>, represents as our `PeerId`.
 // The URL for the telemetry server.
@@ -33,6 +34,26 @@ pub fn get_account_id_from_seed<TPublic: Public>(seed: &str) -> AccountId where
 	AccountPublic::from(get_from_seed::<TPublic>(seed)).into_account()
 }
 
+/// The schedule of the contracts pallet at genesis.
+///
+/// The `CONTRACTS_SCHEDULE` environment variable overrides `preset`. It is either the name of a
+/// preset (`default`, `dev` or `large-contracts`) or the path to a JSON encoded schedule. An
+/// unreadable or malformed schedule is returned as an error rather than ignored.
+pub fn contracts_schedule(preset: SchedulePreset) -> Result<Schedule<Runtime>, String> {
+	let schedule = match std::env::var("CONTRACTS_SCHEDULE") {
+		Ok(schedule) => schedule,
+		Err(std::env::VarError::NotPresent) => return Ok(preset.into()),
+		Err(e) => return Err(format!("Error reading CONTRACTS_SCHEDULE: {}", e)),
+	};
+	if let Ok(preset) = schedule.parse::<SchedulePreset>() {
+		return Ok(preset.into());
+	}
+	let json = std::fs::read(&schedule)
+		.map_err(|e| format!("Error reading schedule file {}: {}", schedule, e))?;
+	serde_json::from_slice(&json)
+		.map_err(|e| format!("Error parsing schedule file {}: {}", schedule, e))
+}
+
 /// Generate an Aura authority key.
 pub fn authority_keys_from_seed(s: &str) -> (AuraId, GrandpaId) {
 	(
@@ -43,6 +64,7 @@ pub fn authority_keys_from_seed(s: &str) -> (AuraId, GrandpaId) {
 
 pub fn development_config() -> Result<ChainSpec, String> {
 	let wasm_binary = WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?;
+	let schedule = contracts_schedule(SchedulePreset::Development)?;
 
 	Ok(ChainSpec::from_genesis(
 		// Name
@@ -65,7 +87,7 @@ pub fn development_config() -> Result<ChainSpec, String> {
 				get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
 				get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
 			],
-			true,
+			schedule.clone(),
 		),
 		// Bootnodes
 		vec![],
@@ -82,6 +104,8 @@ pub fn development_config() -> Result<ChainSpec, String> {
 
 pub fn local_testnet_config() -> Result<ChainSpec, String> {
 	let wasm_binary = WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?;
+	// The local testnet is permissioned, so its deployers may upload large contracts.
+	let schedule = contracts_schedule(SchedulePreset::LargeContracts)?;
 
 	Ok(ChainSpec::from_genesis(
 		// Name
@@ -113,7 +137,7 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 				get_account_id_from_seed::<sr25519::Public>("Eve//stash"),
 				get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
 			],
-			true,
+			schedule.clone(),
 		),
 		// Bootnodes
 		vec![],
@@ -134,7 +158,7 @@ fn testnet_genesis(
 	initial_authorities: Vec<(AuraId, GrandpaId)>,
 	root_key: AccountId,
 	endowed_accounts: Vec<AccountId>,
-	_enable_println: bool,
+	contracts_schedule: Schedule<Runtime>,
 ) -> GenesisConfig {
 	GenesisConfig {
 		frame_system: SystemConfig {
@@ -157,11 +181,8 @@ fn testnet_genesis(
 			key: root_key,
 		},
 		pallet_contracts: ContractsConfig {
-			// TODO - it is necessary to update the scheduler with my own
-			// println should only be enabled on development chains
-			current_schedule: pallet_contracts::Schedule::default()
-			   .enable_println(_enable_println),
-		 },
+			current_schedule: contracts_schedule,
+		},
 		pallet_node_authorization: NodeAuthorizationConfig {
             nodes: vec![
                 (
@@ -17,6 +17,8 @@ pub use sc_rpc_api::DenyUnsafe;
 use sp_transaction_pool::TransactionPool;
 
 use pallet_contracts_rpc::{Contracts, ContractsApi};
+use rayniel95_pallet_sudo_smart_contracts_rpc::{SudoContracts, SudoContractsApi};
+use pallet_audit_log_rpc::{AuditLog, AuditLogApi};
 
 /// Full client dependencies.
 pub struct FullDeps<C, P> {
@@ -26,6 +28,8 @@ pub struct FullDeps<C, P> {
 	pub pool: Arc<P>,
 	/// Whether to deny unsafe calls
 	pub deny_unsafe: DenyUnsafe,
+	/// The number of blocks after which GRANDPA stores a justification.
+	pub justification_period: u32,
 }
 
 /// Instantiate all full RPC extensions.
@@ -35,10 +39,13 @@ pub fn create_full<C, P>(
 	C: ProvideRuntimeApi<Block>,
 	C: HeaderBackend<Block> + HeaderMetadata<Block, Error=BlockChainError> + 'static,
 	C: Send + Sync + 'static,
+	C: sc_client_api::BlockBackend<Block> + sc_client_api::ProofProvider<Block>,
 	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
 	C::Api: BlockBuilder<Block>,
 	C::Api: pallet_contracts_rpc::ContractsRuntimeApi<Block, AccountId, Balance, BlockNumber, Hash>,
+	C::Api: rayniel95_pallet_sudo_smart_contracts_rpc::SudoContractsRuntimeApi<Block, AccountId, Balance, BlockNumber, Hash>,
+	C::Api: pallet_audit_log_rpc::AuditLogRuntimeApi<Block, AccountId, BlockNumber, Hash>,
 	P: TransactionPool + 'static,
 {
 	use substrate_frame_rpc_system::{FullSystem, SystemApi};
@@ -49,6 +56,7 @@ pub fn create_full<C, P>(
 		client,
 		pool,
 		deny_unsafe,
+		justification_period,
 	} = deps;
 
 	io.extend_with(
@@ -68,5 +76,15 @@ pub fn create_full<C, P>(
 		ContractsApi::to_delegate(Contracts::new(client.clone()))
 	);
 
+	io.extend_with(
+		SudoContractsApi::to_delegate(
+			SudoContracts::new(client.clone(), deny_unsafe, justification_period)
+		)
+	);
+
+	io.extend_with(
+		AuditLogApi::to_delegate(AuditLog::new(client.clone()))
+	);
+
 	io
 }
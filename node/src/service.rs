@@ -14,6 +14,9 @@ use sc_finality_grandpa::SharedVoterState;
 use sc_keystore::LocalKeystore;
 use sc_telemetry::{Telemetry, TelemetryWorker};
 
+/// The number of blocks after which GRANDPA stores a justification.
+const GRANDPA_JUSTIFICATION_PERIOD: u32 = 512;
+
 // Our native executor instance.
 native_executor_instance!(
 	pub Executor,
@@ -184,6 +187,7 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 				client: client.clone(),
 				pool: pool.clone(),
 				deny_unsafe,
+				justification_period: GRANDPA_JUSTIFICATION_PERIOD,
 			};
 
 			crate::rpc::create_full(deps)
@@ -254,7 +258,7 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 	let grandpa_config = sc_finality_grandpa::Config {
 		// FIXME #1578 make this available through chainspec
 		gossip_duration: Duration::from_millis(333),
-		justification_period: 512,
+		justification_period: GRANDPA_JUSTIFICATION_PERIOD,
 		name: Some(name),
 		observer_enabled: false,
 		keystore,
@@ -0,0 +1,26 @@
+//! Runtime API definition required by the audit log RPC extensions.
+//!
+//! This API should be imported and implemented by the runtime of a node that wants to page
+//! through the audit log over RPC.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+pub use pallet_audit_log::{ActionKind, AuditEntry, AuditFilter, AuditPage};
+
+sp_api::decl_runtime_apis! {
+	/// The API to read the audit log.
+	pub trait AuditLogApi<AccountId, BlockNumber, Hash> where
+		AccountId: Codec,
+		BlockNumber: Codec,
+		Hash: Codec,
+	{
+		/// Returns up to `limit` records selected by `filter` that are older than the record at
+		/// `before`, newest first. Pass `None` as `before` to start at the newest record.
+		fn entries(
+			filter: AuditFilter<AccountId>,
+			before: Option<u64>,
+			limit: u32,
+		) -> AuditPage<AccountId, BlockNumber, Hash>;
+	}
+}
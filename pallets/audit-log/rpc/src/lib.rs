@@ -0,0 +1,88 @@
+//! Node-specific RPC methods for reading the audit log.
+
+use std::sync::Arc;
+
+use codec::Codec;
+use jsonrpc_core::{Error, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+pub use pallet_audit_log_rpc_runtime_api::{
+	ActionKind, AuditEntry, AuditFilter, AuditPage, AuditLogApi as AuditLogRuntimeApi,
+};
+
+const RUNTIME_ERROR: i64 = 1;
+
+/// Audit log RPC methods.
+#[rpc]
+pub trait AuditLogApi<BlockHash, AccountId, BlockNumber, Hash> {
+	/// Returns up to `limit` records selected by `filter` that are older than the record at
+	/// `before`, newest first.
+	///
+	/// Pass `null` as `before` to start at the newest record and the `next` field of the
+	/// returned page to get the following one. Every page reads a bounded number of records,
+	/// so a page with fewer than `limit` records can still be followed by another one.
+	#[rpc(name = "auditLog_entries")]
+	fn entries(
+		&self,
+		filter: AuditFilter<AccountId>,
+		before: Option<u64>,
+		limit: u32,
+		at: Option<BlockHash>,
+	) -> Result<AuditPage<AccountId, BlockNumber, Hash>>;
+}
+
+/// An implementation of audit log specific RPC methods.
+pub struct AuditLog<C, B> {
+	client: Arc<C>,
+	_marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> AuditLog<C, B> {
+	/// Create new `AuditLog` with the given reference to the client.
+	pub fn new(client: Arc<C>) -> Self {
+		AuditLog {
+			client,
+			_marker: Default::default(),
+		}
+	}
+}
+
+impl<C, Block, AccountId, BlockNumber, Hash>
+	AuditLogApi<<Block as BlockT>::Hash, AccountId, BlockNumber, Hash>
+	for AuditLog<C, Block>
+where
+	Block: BlockT,
+	C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+	C::Api: AuditLogRuntimeApi<Block, AccountId, BlockNumber, Hash>,
+	AccountId: Codec,
+	BlockNumber: Codec,
+	Hash: Codec,
+{
+	fn entries(
+		&self,
+		filter: AuditFilter<AccountId>,
+		before: Option<u64>,
+		limit: u32,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<AuditPage<AccountId, BlockNumber, Hash>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash
+		));
+
+		api.entries(&at, filter, before, limit).map_err(runtime_error_into_rpc_err)
+	}
+}
+
+/// Converts a runtime trap into an RPC error.
+fn runtime_error_into_rpc_err(err: impl std::fmt::Debug) -> Error {
+	Error {
+		code: ErrorCode::ServerError(RUNTIME_ERROR),
+		message: "Runtime error".into(),
+		data: Some(format!("{:?}", err).into()),
+	}
+}
@@ -0,0 +1,76 @@
+//! The signed extension that records privileged calls.
+//!
+//! The record is written in `post_dispatch`, where the result of the call is known. Its few
+//! storage accesses are not part of the declared weight of the call. Privileged calls are rare
+//! and submitted by trusted accounts, so this is accepted instead of charging every call for it.
+
+use crate::{ActionKind, ClassifyCall, Config, Pallet};
+use codec::{Encode, Decode};
+use sp_runtime::{
+	DispatchResult,
+	traits::{DispatchInfoOf, Hash, PostDispatchInfoOf, SignedExtension},
+	transaction_validity::TransactionValidityError,
+};
+use sp_std::{fmt, marker::PhantomData};
+
+/// Records the calls that `T::Classifier` deems privileged in the audit log.
+#[derive(Encode, Decode, Clone, Eq, PartialEq)]
+pub struct RecordPrivileged<T: Config + Send + Sync>(PhantomData<T>);
+
+impl<T: Config + Send + Sync> RecordPrivileged<T> {
+	/// Create a new instance of the extension.
+	pub fn new() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<T: Config + Send + Sync> Default for RecordPrivileged<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T: Config + Send + Sync> fmt::Debug for RecordPrivileged<T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "RecordPrivileged")
+	}
+}
+
+impl<T: Config + Send + Sync> SignedExtension for RecordPrivileged<T>
+where
+	<T as frame_system::Config>::Call: Encode,
+{
+	const IDENTIFIER: &'static str = "RecordPrivileged";
+	type AccountId = T::AccountId;
+	type Call = <T as frame_system::Config>::Call;
+	type AdditionalSigned = ();
+	type Pre = Option<(T::AccountId, ActionKind, T::Hash)>;
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		Ok(T::Classifier::classify(who, call)
+			.map(|kind| (who.clone(), kind, T::Hashing::hash_of(call))))
+	}
+
+	fn post_dispatch(
+		pre: Self::Pre,
+		_info: &DispatchInfoOf<Self::Call>,
+		_post_info: &PostDispatchInfoOf<Self::Call>,
+		_len: usize,
+		result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		if let Some((actor, kind, call_hash)) = pre {
+			<Pallet<T>>::record(actor, kind, call_hash, *result);
+		}
+		Ok(())
+	}
+}
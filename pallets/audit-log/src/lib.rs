@@ -0,0 +1,251 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Audit Log Pallet
+//!
+//! Events are pruned from the state every block, so they cannot tell who deployed a contract or
+//! changed the schedule months ago. This pallet keeps a record of every privileged call in
+//! storage instead.
+//!
+//! Which calls are privileged is decided by the runtime through [`Config::Classifier`]. It sees
+//! the signer as well as the call, so calls of accounts without privileges are not recorded and
+//! cannot push the records of privileged accounts out of the log. The [`RecordPrivileged`]
+//! signed extension writes the record once the call was dispatched, so failed attempts of
+//! privileged accounts are recorded as well. A record holds the signer, the kind of action, the hash
+//! of the call, the block and the result of the dispatch.
+//!
+//! Records are never modified. The log keeps the last [`Config::MaxEntries`] records: once it is
+//! full, every new record drops the oldest one. [`Pallet::page`] pages through the log from the
+//! newest record backwards, optionally filtered by actor or kind of action.
+
+pub use pallet::*;
+pub use extension::RecordPrivileged;
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+pub mod extension;
+
+use codec::{Encode, Decode};
+#[cfg(feature = "std")]
+use serde::{Serialize, Deserialize};
+use sp_runtime::{DispatchResult, RuntimeDebug};
+use sp_std::vec::Vec;
+
+/// The kind of a privileged action.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum ActionKind {
+	/// Uploading, approving or instantiating contract code.
+	Deployment,
+	/// Replacing the code of a live contract.
+	CodeUpgrade,
+	/// Changing the schedule of the contracts pallet.
+	ScheduleUpdate,
+	/// Managing deployers, quotas, storage limits, access control lists, relayers, scheduled
+	/// calls, contract names or the pause state of contracts.
+	ContractAdmin,
+	/// Managing well-known nodes and their connections.
+	NodeAuthorization,
+	/// Any other call made through the sudo key or changing the sudo key.
+	Sudo,
+}
+
+/// Decides which calls are privileged and therefore recorded.
+pub trait ClassifyCall<AccountId, Call> {
+	/// Returns the kind of `call` signed by `who` or `None` if it is not recorded.
+	///
+	/// Calls of accounts that hold no privileges should not be recorded. Every record drops the
+	/// oldest one once the log is full, so anyone who can get a call recorded can flush the log.
+	fn classify(who: &AccountId, call: &Call) -> Option<ActionKind>;
+}
+
+impl<AccountId, Call> ClassifyCall<AccountId, Call> for () {
+	fn classify(_who: &AccountId, _call: &Call) -> Option<ActionKind> {
+		None
+	}
+}
+
+/// The record of a privileged call.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct AuditEntry<AccountId, BlockNumber, Hash> {
+	/// The signer of the extrinsic.
+	pub actor: AccountId,
+	/// The kind of the call.
+	pub kind: ActionKind,
+	/// The hash of the SCALE encoded call.
+	pub call_hash: Hash,
+	/// The block in which the call was dispatched.
+	pub block: BlockNumber,
+	/// The result of the dispatch.
+	///
+	/// Calls made through `pallet_sudo` succeed even if the call they wrap fails. The result of
+	/// the wrapped call is reported by the `Sudid` event of the same extrinsic.
+	pub result: DispatchResult,
+}
+
+/// Selects the records returned by [`Pallet::page`]. Fields that are `None` match any record.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct AuditFilter<AccountId> {
+	/// Only return the records of this account.
+	pub actor: Option<AccountId>,
+	/// Only return the records of this kind of action.
+	pub kind: Option<ActionKind>,
+}
+
+impl<AccountId: PartialEq> AuditFilter<AccountId> {
+	/// Whether `entry` is selected by this filter.
+	pub fn matches<BlockNumber, Hash>(&self, entry: &AuditEntry<AccountId, BlockNumber, Hash>) -> bool {
+		self.actor.as_ref().map_or(true, |actor| *actor == entry.actor) &&
+			self.kind.map_or(true, |kind| kind == entry.kind)
+	}
+}
+
+/// A page of the audit log returned by [`Pallet::page`].
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct AuditPage<AccountId, BlockNumber, Hash> {
+	/// The selected records as `(index, record)`, newest first.
+	pub entries: Vec<(u64, AuditEntry<AccountId, BlockNumber, Hash>)>,
+	/// The `before` argument that returns the next page or `None` if the log holds no older
+	/// records. A page that is not full can still have a `next` page.
+	pub next: Option<u64>,
+}
+
+/// An [`AuditEntry`] of the runtime.
+pub type AuditEntryOf<T> = AuditEntry<
+	<T as frame_system::Config>::AccountId,
+	<T as frame_system::Config>::BlockNumber,
+	<T as frame_system::Config>::Hash,
+>;
+
+/// An [`AuditPage`] of the runtime.
+pub type AuditPageOf<T> = AuditPage<
+	<T as frame_system::Config>::AccountId,
+	<T as frame_system::Config>::BlockNumber,
+	<T as frame_system::Config>::Hash,
+>;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// Decides which calls of the runtime are recorded.
+		type Classifier: ClassifyCall<Self::AccountId, <Self as frame_system::Config>::Call>;
+
+		/// The number of records kept. Older records are dropped.
+		#[pallet::constant]
+		type MaxEntries: Get<u64>;
+
+		/// The maximum number of records returned by [`Pallet::page`].
+		#[pallet::constant]
+		type MaxPageSize: Get<u32>;
+
+		/// The maximum number of records read by [`Pallet::page`], including those that do not
+		/// match the filter.
+		#[pallet::constant]
+		type MaxPageScan: Get<u32>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {}
+
+	#[pallet::event]
+	#[pallet::metadata(T::AccountId = "AccountId")]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A privileged call was recorded. [index, actor, kind]
+		ActionRecorded(u64, T::AccountId, ActionKind),
+	}
+
+	/// The records of the log by their index.
+	#[pallet::storage]
+	#[pallet::getter(fn entry)]
+	pub type Entries<T: Config> = StorageMap<_, Twox64Concat, u64, AuditEntryOf<T>>;
+
+	/// The index of the next record.
+	#[pallet::storage]
+	#[pallet::getter(fn next_index)]
+	pub type NextIndex<T> = StorageValue<_, u64, ValueQuery>;
+
+	impl<T: Config> Pallet<T> {
+		/// The index of the oldest record that is still kept.
+		pub fn oldest_index() -> u64 {
+			Self::next_index().saturating_sub(T::MaxEntries::get())
+		}
+
+		/// Returns up to `limit` records selected by `filter` that are older than the record at
+		/// `before`, newest first. Pass `None` as `before` to start at the newest record.
+		///
+		/// `limit` is capped at `T::MaxPageSize`. At most `T::MaxPageScan` records are read, so a
+		/// page can hold fewer than `limit` records, or none at all, while `next` still points at
+		/// older ones.
+		pub fn page(
+			filter: AuditFilter<T::AccountId>,
+			before: Option<u64>,
+			limit: u32,
+		) -> AuditPageOf<T> {
+			let limit = limit.min(T::MaxPageSize::get()) as usize;
+			let oldest = Self::oldest_index();
+			let mut index = before.unwrap_or(u64::max_value()).min(Self::next_index());
+			let mut entries = Vec::new();
+			let mut scanned = 0;
+			while index > oldest {
+				if entries.len() == limit || scanned == T::MaxPageScan::get() {
+					return AuditPage { entries, next: Some(index) };
+				}
+				scanned += 1;
+				index -= 1;
+				if let Some(entry) = <Entries<T>>::get(index) {
+					if filter.matches(&entry) {
+						entries.push((index, entry));
+					}
+				}
+			}
+			AuditPage { entries, next: None }
+		}
+
+		/// Appends a record to the log, dropping the oldest one if the log is full.
+		pub(crate) fn record(
+			actor: T::AccountId,
+			kind: ActionKind,
+			call_hash: T::Hash,
+			result: DispatchResult,
+		) {
+			let index = Self::next_index();
+			let entry = AuditEntry {
+				actor: actor.clone(),
+				kind,
+				call_hash,
+				block: <frame_system::Pallet<T>>::block_number(),
+				result,
+			};
+			<Entries<T>>::insert(index, entry);
+			<NextIndex<T>>::put(index.saturating_add(1));
+			if let Some(dropped) = index.checked_sub(T::MaxEntries::get()) {
+				<Entries<T>>::remove(dropped);
+			}
+			Self::deposit_event(Event::ActionRecorded(index, actor, kind));
+		}
+	}
+}
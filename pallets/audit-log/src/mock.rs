@@ -0,0 +1,96 @@
+use crate as pallet_audit_log;
+use crate::{ActionKind, ClassifyCall};
+use sp_core::H256;
+use frame_support::parameter_types;
+use sp_runtime::{
+	traits::{BlakeTwo256, IdentityLookup}, testing::Header,
+};
+use frame_system as system;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		AuditLog: pallet_audit_log::{Pallet, Storage, Event<T>},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 42;
+}
+
+impl system::Config for Test {
+	type BaseCallFilter = ();
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+	type OnSetCode = ();
+}
+
+/// The accounts below this one are privileged.
+pub const UNPRIVILEGED: u64 = 10;
+
+/// Treats `set_heap_pages` as a sudo call and `set_code` as a code upgrade if they are made by
+/// a privileged account.
+pub struct Classifier;
+
+impl ClassifyCall<u64, Call> for Classifier {
+	fn classify(who: &u64, call: &Call) -> Option<ActionKind> {
+		if *who >= UNPRIVILEGED {
+			return None;
+		}
+		match call {
+			Call::System(frame_system::Call::set_heap_pages(_)) => Some(ActionKind::Sudo),
+			Call::System(frame_system::Call::set_code(_)) => Some(ActionKind::CodeUpgrade),
+			_ => None,
+		}
+	}
+}
+
+parameter_types! {
+	pub const MaxEntries: u64 = 4;
+	pub const MaxPageSize: u32 = 3;
+	pub static MaxPageScan: u32 = 4;
+}
+
+impl pallet_audit_log::Config for Test {
+	type Event = Event;
+	type Classifier = Classifier;
+	type MaxEntries = MaxEntries;
+	type MaxPageSize = MaxPageSize;
+	type MaxPageScan = MaxPageScan;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut ext: sp_io::TestExternalities =
+		system::GenesisConfig::default().build_storage::<Test>().unwrap().into();
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
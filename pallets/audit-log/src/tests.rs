@@ -0,0 +1,122 @@
+use crate::{ActionKind, AuditEntry, AuditFilter, Event as AuditLogEvent, RecordPrivileged, mock::*};
+use frame_support::weights::{DispatchInfo, PostDispatchInfo};
+use sp_runtime::{
+	DispatchError, DispatchResult,
+	traits::{BlakeTwo256, Hash, SignedExtension},
+};
+
+fn set_heap_pages(pages: u64) -> Call {
+	Call::System(frame_system::Call::set_heap_pages(pages))
+}
+
+/// Runs `call` of `who` through the extension as if its dispatch returned `result`.
+fn dispatch(who: u64, call: &Call, result: DispatchResult) {
+	let info = DispatchInfo::default();
+	let pre = RecordPrivileged::<Test>::new().pre_dispatch(&who, call, &info, 0).unwrap();
+	RecordPrivileged::<Test>::post_dispatch(pre, &info, &PostDispatchInfo::default(), 0, &result)
+		.unwrap();
+}
+
+fn indices(filter: AuditFilter<u64>, before: Option<u64>, limit: u32) -> (Vec<u64>, Option<u64>) {
+	let page = AuditLog::page(filter, before, limit);
+	(page.entries.into_iter().map(|(index, _)| index).collect(), page.next)
+}
+
+#[test]
+fn privileged_calls_are_recorded() {
+	new_test_ext().execute_with(|| {
+		let call = set_heap_pages(1);
+		dispatch(1, &call, Ok(()));
+		System::set_block_number(2);
+		dispatch(2, &Call::System(frame_system::Call::set_code(vec![])), Err(DispatchError::BadOrigin));
+
+		assert_eq!(AuditLog::entry(0), Some(AuditEntry {
+			actor: 1,
+			kind: ActionKind::Sudo,
+			call_hash: BlakeTwo256::hash_of(&call),
+			block: 1,
+			result: Ok(()),
+		}));
+		let entry = AuditLog::entry(1).unwrap();
+		assert_eq!((entry.actor, entry.kind, entry.block), (2, ActionKind::CodeUpgrade, 2));
+		assert_eq!(entry.result, Err(DispatchError::BadOrigin));
+		assert_eq!(
+			System::events().pop().unwrap().event,
+			Event::pallet_audit_log(AuditLogEvent::ActionRecorded(1, 2, ActionKind::CodeUpgrade)),
+		);
+	});
+}
+
+#[test]
+fn other_calls_are_not_recorded() {
+	new_test_ext().execute_with(|| {
+		dispatch(1, &Call::System(frame_system::Call::remark(vec![1])), Ok(()));
+		assert_eq!(AuditLog::next_index(), 0);
+		assert_eq!(AuditLog::entry(0), None);
+	});
+}
+
+#[test]
+fn calls_of_unprivileged_accounts_cannot_flush_the_log() {
+	new_test_ext().execute_with(|| {
+		dispatch(1, &set_heap_pages(0), Ok(()));
+		for pages in 0..10 {
+			dispatch(UNPRIVILEGED, &set_heap_pages(pages), Err(DispatchError::BadOrigin));
+		}
+		assert_eq!(AuditLog::next_index(), 1);
+		assert_eq!(AuditLog::entry(0).map(|entry| entry.actor), Some(1));
+	});
+}
+
+#[test]
+fn oldest_entries_are_dropped() {
+	new_test_ext().execute_with(|| {
+		for pages in 0..6 {
+			dispatch(1, &set_heap_pages(pages), Ok(()));
+		}
+		assert_eq!(AuditLog::next_index(), 6);
+		assert_eq!(AuditLog::oldest_index(), 2);
+		assert_eq!(AuditLog::entry(1), None);
+		assert!(AuditLog::entry(2).is_some());
+		assert_eq!(indices(Default::default(), None, 10), (vec![5, 4, 3], Some(3)));
+		assert_eq!(indices(Default::default(), Some(3), 10), (vec![2], None));
+	});
+}
+
+#[test]
+fn pages_are_filtered_by_actor_and_kind() {
+	new_test_ext().execute_with(|| {
+		dispatch(1, &set_heap_pages(0), Ok(()));
+		dispatch(2, &set_heap_pages(1), Ok(()));
+		dispatch(1, &Call::System(frame_system::Call::set_code(vec![])), Ok(()));
+		dispatch(1, &set_heap_pages(2), Ok(()));
+
+		let by_actor = AuditFilter { actor: Some(1), kind: None };
+		assert_eq!(indices(by_actor.clone(), None, 2), (vec![3, 2], Some(2)));
+		assert_eq!(indices(by_actor, Some(2), 2), (vec![0], None));
+
+		let by_kind = AuditFilter { actor: None, kind: Some(ActionKind::Sudo) };
+		assert_eq!(indices(by_kind, None, 3), (vec![3, 1, 0], None));
+
+		let both = AuditFilter { actor: Some(2), kind: Some(ActionKind::CodeUpgrade) };
+		assert_eq!(indices(both, None, 3), (vec![], None));
+	});
+}
+
+#[test]
+fn pages_read_a_bounded_number_of_records() {
+	new_test_ext().execute_with(|| {
+		MAX_PAGE_SCAN.with(|v| *v.borrow_mut() = 2);
+		dispatch(1, &set_heap_pages(0), Ok(()));
+		dispatch(2, &set_heap_pages(1), Ok(()));
+		dispatch(2, &set_heap_pages(2), Ok(()));
+		dispatch(1, &set_heap_pages(3), Ok(()));
+
+		let by_actor = AuditFilter { actor: Some(2), kind: None };
+		assert_eq!(indices(by_actor.clone(), None, 3), (vec![2], Some(2)));
+		assert_eq!(indices(by_actor, Some(2), 3), (vec![1], None));
+
+		let nobody = AuditFilter { actor: Some(3), kind: None };
+		assert_eq!(indices(nobody, None, 3), (vec![], Some(2)));
+	});
+}
@@ -173,6 +173,7 @@ where
 				&info.trie_id,
 				&item.0,
 				Some(item.1.clone()),
+				None,
 			)
 			.map_err(|_| "Failed to write storage to restoration dest")?;
 		}
@@ -542,6 +543,28 @@ benchmarks! {
 		let origin = RawOrigin::Signed(instance.caller.clone());
 	}: call(origin, instance.addr, 0u32.into(), Weight::max_value(), vec![])
 
+	// Benchmarks the call as executed on-chain where debug messages are not recorded.
+	seal_debug_message {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let code = WasmModule::<T>::from(ModuleDefinition {
+			memory: Some(ImportedMemory::max::<T>()),
+			imported_functions: vec![ImportedFunction {
+				name: "seal_debug_message",
+				params: vec![ValueType::I32, ValueType::I32],
+				return_type: Some(ValueType::I32),
+			}],
+			call_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+				Instruction::I32Const(0), // str_ptr
+				Instruction::I32Const(0), // str_len
+				Instruction::Call(0),
+				Instruction::Drop,
+			])),
+			.. Default::default()
+		});
+		let instance = Contract::<T>::new(code, vec![], Endow::Max)?;
+		let origin = RawOrigin::Signed(instance.caller.clone());
+	}: call(origin, instance.addr, 0u32.into(), Weight::max_value(), vec![])
+
 	seal_weight_to_fee {
 		let r in 0 .. API_BENCHMARK_BATCHES;
 		let pages = code::max_pages::<T>();
@@ -1154,7 +1177,8 @@ benchmarks! {
 				&instance.account_id,
 				&trie_id,
 				key.as_slice().try_into().map_err(|e| "Key has wrong length")?,
-				Some(vec![42; T::MaxValueSize::get() as usize])
+				Some(vec![42; T::MaxValueSize::get() as usize]),
+				None,
 			)
 			.map_err(|_| "Failed to write to storage during setup.")?;
 		}
@@ -1199,7 +1223,8 @@ benchmarks! {
 				&instance.account_id,
 				&trie_id,
 				key.as_slice().try_into().map_err(|e| "Key has wrong length")?,
-				Some(vec![])
+				Some(vec![]),
+				None,
 			)
 			.map_err(|_| "Failed to write to storage during setup.")?;
 		}
@@ -1243,7 +1268,8 @@ benchmarks! {
 			&instance.account_id,
 			&trie_id,
 			key.as_slice().try_into().map_err(|e| "Key has wrong length")?,
-			Some(vec![42u8; (n * 1024) as usize])
+			Some(vec![42u8; (n * 1024) as usize]),
+			None,
 		)
 		.map_err(|_| "Failed to write to storage during setup.")?;
 		let origin = RawOrigin::Signed(instance.caller.clone());
@@ -17,13 +17,16 @@
 
 use crate::{
 	CodeHash, Event, Config, Pallet as Contracts,
-	TrieId, BalanceOf, ContractInfo, gas::GasMeter, rent::Rent, storage::{self, Storage},
-	Error, ContractInfoOf, Schedule, AliveContractInfo,
+	TrieId, BalanceOf, ContractInfo, gas::GasMeter, rent::Rent,
+	storage::{self, Storage, StorageLimit},
+	Error, ContractInfoOf, Schedule, AliveContractInfo, PendingCode,
+	trace::{CallKind, Tracer},
 };
 use sp_core::crypto::UncheckedFrom;
 use sp_std::{
 	prelude::*,
 	marker::PhantomData,
+	cell::RefCell,
 };
 use sp_runtime::{Perbill, traits::{Bounded, Zero, Convert, Saturating}};
 use frame_support::{
@@ -49,7 +52,9 @@ pub type TopicOf<T> = <T as frame_system::Config>::Hash;
 /// Call or instantiate both called into other contracts and pass through errors happening
 /// in those to the caller. This enum is for the caller to distinguish whether the error
 /// happened during the execution of the callee or in the current execution context.
-#[cfg_attr(test, derive(Debug, PartialEq))]
+#[cfg_attr(feature = "std", derive(serde::Serialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(codec::Encode, codec::Decode, Clone, Copy, PartialEq, Eq, sp_runtime::RuntimeDebug)]
 pub enum ErrorOrigin {
 	/// Caller error origin.
 	///
@@ -305,6 +310,17 @@ pub trait Ext: sealing::Sealed {
 
 	/// Information needed for rent calculations.
 	fn rent_params(&self) -> &RentParams<Self::T>;
+
+	/// Append a string to the debug buffer.
+	///
+	/// It is added as-is without any additional new line. Messages that would push the
+	/// buffer beyond [`Config::MaxDebugBufferLen`] are dropped.
+	///
+	/// This is a no-op if debug message recording is disabled which is always the case
+	/// when the code is executing on-chain.
+	///
+	/// Returns `true` if debug message recording is enabled. Otherwise `false` is returned.
+	fn append_debug_buffer(&mut self, msg: &str) -> bool;
 }
 
 /// Describes the different functions that can be exported by an [`Executable`].
@@ -394,10 +410,14 @@ pub struct ExecutionContext<'a, T: Config + 'a, E> {
 	caller: Option<&'a ExecutionContext<'a, T, E>>,
 	self_account: T::AccountId,
 	self_trie_id: Option<TrieId>,
+	/// The storage limit of `self_account`, read once when the context is created.
+	self_storage_limit: Option<StorageLimit>,
 	depth: usize,
 	schedule: &'a Schedule<T>,
 	timestamp: MomentOf<T>,
 	block_number: T::BlockNumber,
+	debug_message: Option<&'a RefCell<Vec<u8>>>,
+	tracer: Option<&'a RefCell<Tracer<T>>>,
 	_phantom: PhantomData<E>,
 }
 
@@ -415,36 +435,120 @@ where
 		ExecutionContext {
 			caller: None,
 			self_trie_id: None,
+			self_storage_limit: None,
 			self_account: origin,
 			depth: 0,
 			schedule,
 			timestamp: T::Time::now(),
 			block_number: <frame_system::Pallet<T>>::block_number(),
+			debug_message: None,
+			tracer: None,
 			_phantom: Default::default(),
 		}
 	}
 
+	/// Record all messages passed to `seal_debug_message` into `buffer`.
+	///
+	/// The buffer is shared with all nested contexts. It must only be attached for
+	/// off-chain executions (RPC dry runs) as on-chain execution must not depend on it.
+	pub fn with_debug_buffer(mut self, buffer: &'a RefCell<Vec<u8>>) -> Self {
+		self.debug_message = Some(buffer);
+		self
+	}
+
+	/// Record the call tree of the execution into `tracer`.
+	///
+	/// Like the debug buffer this must only be used for off-chain executions.
+	pub fn with_tracer(mut self, tracer: &'a RefCell<Tracer<T>>) -> Self {
+		self.tracer = Some(tracer);
+		self
+	}
+
+	/// Make a call to the specified address, optionally transferring some funds.
+	///
+	/// # Return Value
+	///
+	/// Result<(ExecReturnValue, CodeSize), (ExecError, CodeSize)>
+	pub fn call(
+		&mut self,
+		dest: T::AccountId,
+		value: BalanceOf<T>,
+		gas_meter: &mut GasMeter<T>,
+		input_data: Vec<u8>,
+	) -> Result<(ExecReturnValue, u32), (ExecError, u32)> {
+		let tracer = match self.tracer {
+			Some(tracer) => tracer,
+			None => return self.do_call(dest, value, gas_meter, input_data),
+		};
+		tracer.borrow_mut().enter(
+			CallKind::Call, self.self_account.clone(), dest.clone(), value, &input_data,
+		);
+		let gas_before = gas_meter.gas_spent();
+		let result = self.do_call(dest, value, gas_meter, input_data);
+		tracer.borrow_mut().exit(
+			result.as_ref().map(|r| &r.0).map_err(|e| &e.0),
+			gas_meter.gas_spent().saturating_sub(gas_before),
+		);
+		result
+	}
+
+	/// Instantiate a new contract from the given executable.
+	///
+	/// The address of the new contract is derived from the current account, the code hash of
+	/// `executable` and `salt`.
+	pub fn instantiate(
+		&mut self,
+		endowment: BalanceOf<T>,
+		gas_meter: &mut GasMeter<T>,
+		executable: E,
+		input_data: Vec<u8>,
+		salt: &[u8],
+	) -> Result<(T::AccountId, ExecReturnValue), ExecError> {
+		let tracer = match self.tracer {
+			Some(tracer) => tracer,
+			None => return self.do_instantiate(endowment, gas_meter, executable, input_data, salt),
+		};
+		let code_hash = executable.code_hash().clone();
+		let dest = Contracts::<T>::contract_address(&self.self_account, &code_hash, salt);
+		tracer.borrow_mut().enter(
+			CallKind::Instantiate, self.self_account.clone(), dest, endowment, &input_data,
+		);
+		tracer.borrow_mut().code_hash(code_hash);
+		let gas_before = gas_meter.gas_spent();
+		let result = self.do_instantiate(endowment, gas_meter, executable, input_data, salt);
+		tracer.borrow_mut().exit(
+			result.as_ref().map(|r| &r.1),
+			gas_meter.gas_spent().saturating_sub(gas_before),
+		);
+		result
+	}
+
+	/// Pass a record to the tracer if tracing is enabled.
+	fn trace(&self, f: impl FnOnce(&mut Tracer<T>)) {
+		if let Some(tracer) = self.tracer {
+			f(&mut tracer.borrow_mut());
+		}
+	}
+
 	fn nested<'b, 'c: 'b>(&'c self, dest: T::AccountId, trie_id: TrieId)
 		-> ExecutionContext<'b, T, E>
 	{
 		ExecutionContext {
 			caller: Some(self),
 			self_trie_id: Some(trie_id),
+			self_storage_limit: Contracts::<T>::storage_limit(&dest),
 			self_account: dest,
 			depth: self.depth + 1,
 			schedule: self.schedule,
 			timestamp: self.timestamp.clone(),
 			block_number: self.block_number.clone(),
+			debug_message: self.debug_message,
+			tracer: self.tracer,
 			_phantom: Default::default(),
 		}
 	}
 
-	/// Make a call to the specified address, optionally transferring some funds.
-	///
-	/// # Return Value
-	///
-	/// Result<(ExecReturnValue, CodeSize), (ExecError, CodeSize)>
-	pub fn call(
+	fn do_call(
 		&mut self,
 		dest: T::AccountId,
 		value: BalanceOf<T>,
@@ -455,9 +559,14 @@ where
 			return Err((Error::<T>::MaxCallDepthReached.into(), 0));
 		}
 
+		if Contracts::<T>::is_paused(&dest) {
+			return Err((Error::<T>::ContractPaused.into(), 0));
+		}
+
 		let contract = <ContractInfoOf<T>>::get(&dest)
 			.and_then(|contract| contract.get_alive())
 			.ok_or((Error::<T>::NotCallable.into(), 0))?;
+		self.trace(|tracer| tracer.code_hash(contract.code_hash));
 
 		let executable = E::from_storage(contract.code_hash, &self.schedule, gas_meter)
 			.map_err(|e| (e.into(), 0))?;
@@ -501,7 +610,7 @@ where
 		Ok((result, code_len))
 	}
 
-	pub fn instantiate(
+	fn do_instantiate(
 		&mut self,
 		endowment: BalanceOf<T>,
 		gas_meter: &mut GasMeter<T>,
@@ -513,6 +622,15 @@ where
 			Err(Error::<T>::MaxCallDepthReached)?
 		}
 
+		if Contracts::<T>::is_halted() {
+			Err(Error::<T>::ContractPaused)?
+		}
+
+		// Uploaded code needs to be reviewed before contracts can be created from it.
+		if <PendingCode<T>>::contains_key(executable.code_hash()) {
+			Err(Error::<T>::CodeNotApproved)?
+		}
+
 		let transactor_kind = self.transactor_kind();
 		let caller = self.self_account.clone();
 		let dest = Contracts::<T>::contract_address(&caller, executable.code_hash(), salt);
@@ -740,7 +858,9 @@ where
 				expect can't fail;\
 				qed",
 		);
-		Storage::<T>::read(trie_id, key)
+		let value = Storage::<T>::read(trie_id, key);
+		self.ctx.trace(|tracer| tracer.storage_read(key, value.as_deref()));
+		value
 	}
 
 	fn set_storage(&mut self, key: StorageKey, value: Option<Vec<u8>>) -> DispatchResult {
@@ -755,7 +875,14 @@ where
 		// the contract cannot be absent in storage;
 		// write cannot return `None`;
 		// qed
-		Storage::<T>::write(&self.ctx.self_account, trie_id, &key, value)
+		let traced = self.ctx.tracer.map(|_| (value.clone(), Storage::<T>::read(trie_id, &key)));
+		Storage::<T>::write(
+			&self.ctx.self_account, trie_id, &key, value, self.ctx.self_storage_limit,
+		)?;
+		if let Some((value, previous)) = traced {
+			self.ctx.trace(|tracer| tracer.storage_write(&key, value, previous));
+		}
+		Ok(())
 	}
 
 	fn instantiate(
@@ -897,6 +1024,7 @@ where
 	}
 
 	fn deposit_event(&mut self, topics: Vec<T::Hash>, data: Vec<u8>) {
+		self.ctx.trace(|tracer| tracer.event(&topics, &data));
 		deposit_event::<Self::T>(
 			topics,
 			Event::ContractEmitted(self.ctx.self_account.clone(), data)
@@ -936,6 +1064,20 @@ where
 	fn rent_params(&self) -> &RentParams<Self::T> {
 		&self.rent_params
 	}
+
+	fn append_debug_buffer(&mut self, msg: &str) -> bool {
+		if let Some(buffer) = self.ctx.debug_message {
+			let mut buffer = buffer.borrow_mut();
+			let fits = buffer.len().saturating_add(msg.len()) <=
+				T::MaxDebugBufferLen::get() as usize;
+			if fits {
+				buffer.extend_from_slice(msg.as_bytes());
+			}
+			true
+		} else {
+			false
+		}
+	}
 }
 
 fn deposit_event<T: Config>(
@@ -978,6 +1120,7 @@ mod tests {
 			test_utils::{place_contract, set_balance, get_balance},
 		},
 		exec::ExportedFunction::*,
+		trace::{StorageAccess, EmittedEvent},
 		Error, Weight, CurrentSchedule,
 	};
 	use sp_core::Bytes;
@@ -1848,4 +1991,104 @@ mod tests {
 			).unwrap();
 		});
 	}
+
+	#[test]
+	fn debug_buffer_is_bounded() {
+		let code_hash = MockLoader::insert(Call, |ctx, _| {
+			let too_long = "x".repeat(<Test as Config>::MaxDebugBufferLen::get() as usize);
+			assert!(ctx.ext.append_debug_buffer("Hello"));
+			assert!(ctx.ext.append_debug_buffer(&too_long));
+			assert!(ctx.ext.append_debug_buffer(" World"));
+			exec_success()
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let schedule = <CurrentSchedule<Test>>::get();
+			let debug_buffer = RefCell::new(Vec::new());
+			let mut ctx = MockContext::top_level(ALICE, &schedule)
+				.with_debug_buffer(&debug_buffer);
+			let mut gas_meter = GasMeter::<Test>::new(GAS_LIMIT);
+			place_contract(&BOB, code_hash);
+			ctx.call(BOB, 0, &mut gas_meter, vec![]).unwrap();
+			drop(ctx);
+			assert_eq!(debug_buffer.into_inner(), b"Hello World".to_vec());
+		});
+	}
+
+	#[test]
+	fn debug_buffer_is_disabled_by_default() {
+		let code_hash = MockLoader::insert(Call, |ctx, _| {
+			assert!(!ctx.ext.append_debug_buffer("Hello"));
+			exec_success()
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let schedule = <CurrentSchedule<Test>>::get();
+			let mut ctx = MockContext::top_level(ALICE, &schedule);
+			let mut gas_meter = GasMeter::<Test>::new(GAS_LIMIT);
+			place_contract(&BOB, code_hash);
+			ctx.call(BOB, 0, &mut gas_meter, vec![]).unwrap();
+		});
+	}
+
+	#[test]
+	fn tracer_records_call_tree() {
+		let bob_ch = MockLoader::insert(Call, |ctx, _| {
+			ctx.ext.set_storage([1; 32], Some(vec![42])).unwrap();
+			assert_matches!(ctx.ext.call(&CHARLIE, 0, ctx.gas_meter, vec![7]), Ok(_));
+			exec_success()
+		});
+		let charlie_ch = MockLoader::insert(Call, |ctx, _| {
+			assert_eq!(ctx.ext.get_storage(&[2; 32]), None);
+			ctx.ext.deposit_event(vec![], vec![1, 2, 3]);
+			Err(Error::<Test>::ContractTrapped.into())
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let schedule = <CurrentSchedule<Test>>::get();
+			let tracer = RefCell::new(Tracer::default());
+			let mut ctx = MockContext::top_level(ALICE, &schedule).with_tracer(&tracer);
+			place_contract(&BOB, bob_ch);
+			place_contract(&CHARLIE, charlie_ch);
+
+			let mut gas_meter = GasMeter::<Test>::new(GAS_LIMIT);
+			assert_matches!(ctx.call(BOB, 0, &mut gas_meter, vec![]), Ok(_));
+			drop(ctx);
+
+			let frames = tracer.into_inner().into_frames();
+			assert_eq!(frames.len(), 2);
+
+			let bob = &frames[0];
+			assert_eq!((bob.depth, bob.kind), (0, CallKind::Call));
+			assert_eq!((&bob.caller, &bob.callee), (&ALICE, &BOB));
+			assert_eq!(bob.code_hash, Some(bob_ch));
+			assert_eq!(bob.output, Some(Bytes(Vec::new())));
+			assert_eq!(bob.error, None);
+			assert_eq!(bob.gas_used, gas_meter.gas_spent());
+			assert_eq!(bob.storage, vec![StorageAccess {
+				key: Bytes(vec![1; 32]),
+				value: Some(Bytes(vec![42])),
+				previous: None,
+				write: true,
+			}]);
+
+			let charlie = &frames[1];
+			assert_eq!((charlie.depth, charlie.kind), (1, CallKind::Call));
+			assert_eq!((&charlie.caller, &charlie.callee), (&BOB, &CHARLIE));
+			assert_eq!(charlie.input, Bytes(vec![7]));
+			assert_eq!(charlie.output, None);
+			assert_eq!(charlie.error, Some(Error::<Test>::ContractTrapped.into()));
+			assert_eq!(charlie.error_origin, Some(ErrorOrigin::Callee));
+			assert_eq!(charlie.storage, vec![StorageAccess {
+				key: Bytes(vec![2; 32]),
+				value: None,
+				previous: None,
+				write: false,
+			}]);
+			assert_eq!(charlie.events, vec![EmittedEvent {
+				topics: vec![],
+				data: Bytes(vec![1, 2, 3]),
+			}]);
+		});
+	}
 }
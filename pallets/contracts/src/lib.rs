@@ -60,7 +60,7 @@
 //! ### Dispatchable functions
 //!
 //! * [`Pallet::update_schedule`] -
-//! ([Root Origin](https://substrate.dev/docs/en/knowledgebase/runtime/origin) Only) -
+//! ([Root Origin](https://substrate.dev/docs/en/knowledgebase/runtime/origin) or deployer Only) -
 //! Set a new [`Schedule`].
 //! * [`Pallet::instantiate_with_code`] - Deploys a new contract from the supplied wasm binary,
 //! optionally transferring
@@ -70,6 +70,16 @@
 //! code an existing `code_hash` is supplied.
 //! * [`Pallet::call`] - Makes a call to an account, optionally transferring some balance.
 //! * [`Pallet::claim_surcharge`] - Evict a contract that cannot pay rent anymore.
+//! * [`Pallet::upload_code`] - (Deployer Only) - Store code without instantiating it. The code
+//! needs to be approved before contracts can be instantiated from it.
+//! * [`Pallet::approve_code`] - (Root Only) - Approve uploaded code for instantiation.
+//! * [`Pallet::reject_code`] - (Root Only) - Remove uploaded code that was not approved.
+//! * [`Pallet::add_deployer`] - (Root Only) - Allow an account to deploy contracts and update
+//! the schedule, optionally until a given block.
+//! * [`Pallet::remove_deployer`] - (Root Only) - Revoke the deployment permission of an account.
+//! * [`Pallet::pause_contract`] - (Root Only) - Reject all calls to a contract.
+//! * [`Pallet::unpause_contract`] - (Root Only) - Accept calls to a paused contract again.
+//! * [`Pallet::set_halted`] - (Root Only) - Halt or resume the execution of all contracts.
 //!
 //! ## Usage
 //!
@@ -96,6 +106,7 @@ mod rent;
 mod benchmarking;
 mod schedule;
 mod migration;
+mod trace;
 
 pub mod chain_extension;
 pub mod weights;
@@ -103,7 +114,13 @@ pub mod weights;
 #[cfg(test)]
 mod tests;
 
-pub use crate::{pallet::*, schedule::Schedule};
+pub use crate::{
+	pallet::*,
+	schedule::{HostFnWeights, InstructionWeights, Limits, Schedule, SchedulePreset},
+	storage::{StorageLimit, StorageUsage},
+	exec::ErrorOrigin,
+	trace::{CallFrame, CallFrameOf, CallKind, EmittedEvent, StorageAccess, record_frames},
+};
 use crate::{
 	gas::GasMeter,
 	exec::{ExecutionContext, Executable},
@@ -111,23 +128,25 @@ use crate::{
 	storage::{Storage, DeletedContract, ContractInfo, AliveContractInfo, TombstoneContractInfo},
 	weights::WeightInfo,
 	wasm::PrefabWasmModule,
+	trace::Tracer,
 };
 use sp_core::{Bytes, crypto::UncheckedFrom};
-use sp_std::prelude::*;
+use sp_std::{prelude::*, cell::RefCell};
 use sp_runtime::{
 	traits::{
 		Hash, StaticLookup, Convert, Saturating, Zero,
 	},
-	Perbill,
+	DispatchError, Perbill,
 };
 use frame_support::{
+	dispatch::DispatchResultWithPostInfo,
 	traits::{OnUnbalanced, Currency, Get, Time, Randomness},
 	weights::{Weight, PostDispatchInfo, WithPostDispatchInfo},
 };
 use frame_system::Pallet as System;
 use pallet_contracts_primitives::{
 	RentProjectionResult, GetStorageResult, ContractAccessError, ContractExecResult,
-	ContractInstantiateResult, Code, InstantiateReturnValue,
+	ContractInstantiateResult, Code, InstantiateReturnValue, ExecReturnValue,
 };
 
 type CodeHash<T> = <T as frame_system::Config>::Hash;
@@ -211,6 +230,15 @@ pub mod pallet {
 		#[pallet::constant]
 		type SurchargeReward: Get<BalanceOf<Self>>;
 
+		/// Disables rent, eviction, tombstones and `seal_restore_to`.
+		///
+		/// Contracts then never pay rent and are never evicted, whatever their balance.
+		/// [`Self::RentFraction`] and [`Self::SurchargeReward`] are ignored, while
+		/// [`Pallet::claim_surcharge`] and `seal_restore_to` always fail. Tombstones left from
+		/// before rent was disabled are removed on the next runtime upgrade.
+		#[pallet::constant]
+		type RentFree: Get<bool>;
+
 		/// The maximum nesting level of a call/instantiate stack.
 		#[pallet::constant]
 		type MaxDepth: Get<u32>;
@@ -243,6 +271,16 @@ pub mod pallet {
 		/// a wasm binary below this maximum size.
 		#[pallet::constant]
 		type MaxCodeSize: Get<u32>;
+
+		/// The maximum length of the metadata attached to a code in bytes.
+		#[pallet::constant]
+		type MaxMetadataLen: Get<u32>;
+
+		/// The maximum length of the debug buffer returned by dry runs in bytes.
+		///
+		/// Messages passed to `seal_debug_message` that do not fit anymore are dropped.
+		#[pallet::constant]
+		type MaxDebugBufferLen: Get<u32>;
 	}
 
 	#[pallet::pallet]
@@ -280,16 +318,23 @@ pub mod pallet {
 		/// The schedule's version cannot be less than the version of the stored schedule.
 		/// If a schedule does not change the instruction weights the version does not
 		/// need to be increased. Therefore we allow storing a schedule that has the same
-		/// version as the stored one.
+		/// version as the stored one. The limits of the schedule cannot be lower than the
+		/// limits of the stored schedule because that could break existing contracts.
+		///
+		/// The origin must be root or an authorized deployer. See [`Self::ensure_deployer`].
 		#[pallet::weight(T::WeightInfo::update_schedule())]
 		pub fn update_schedule(
 			origin: OriginFor<T>,
 			schedule: Schedule<T>
 		) -> DispatchResultWithPostInfo {
-			ensure_root(origin)?;
-			if <CurrentSchedule<T>>::get().version > schedule.version {
+			Self::ensure_deployer(origin)?;
+			let current_schedule = <CurrentSchedule<T>>::get();
+			if current_schedule.version > schedule.version {
 				Err(Error::<T>::InvalidScheduleVersion)?
 			}
+			if !schedule.limits.covers(&current_schedule.limits) {
+				Err(Error::<T>::ScheduleLimitsLowered)?
+			}
 			Self::deposit_event(Event::ScheduleUpdated(schedule.version));
 			CurrentSchedule::put(schedule);
 			Ok(().into())
@@ -312,20 +357,14 @@ pub mod pallet {
 		) -> DispatchResultWithPostInfo {
 			let origin = ensure_signed(origin)?;
 			let dest = T::Lookup::lookup(dest)?;
-			let mut gas_meter = GasMeter::new(gas_limit);
-			let schedule = <CurrentSchedule<T>>::get();
-			let mut ctx = ExecutionContext::<T, PrefabWasmModule<T>>::top_level(origin, &schedule);
-			let (result, code_len) = match ctx.call(dest, value, &mut gas_meter, data) {
-				Ok((output, len)) => (Ok(output), len),
-				Err((err, len)) => (Err(err), len),
-			};
-			gas_meter.into_dispatch_result(result, T::WeightInfo::call(code_len / 1024))
+			Self::call_with_output(origin, dest, value, gas_limit, data).0
 		}
 
 		/// Instantiates a new contract from the supplied `code` optionally transferring
 		/// some balance.
 		///
-		/// This is the only function that can deploy new code to the chain.
+		/// This is the only function that can deploy new code to the chain. The origin must be
+		/// an authorized deployer. See [`Self::ensure_deployer`].
 		///
 		/// # Parameters
 		///
@@ -358,22 +397,11 @@ pub mod pallet {
 			data: Vec<u8>,
 			salt: Vec<u8>,
 		) -> DispatchResultWithPostInfo {
-			Self::is_root(origin.clone())?;
+			Self::ensure_deployer(origin.clone())?;
 			let origin = ensure_signed(origin)?;
-			let code_len = code.len() as u32;
-			ensure!(code_len <= T::MaxCodeSize::get(), Error::<T>::CodeTooLarge);
-			let mut gas_meter = GasMeter::new(gas_limit);
-			let schedule = <CurrentSchedule<T>>::get();
-			let executable = PrefabWasmModule::from_code(code, &schedule)?;
-			let code_len = executable.code_len();
-			ensure!(code_len <= T::MaxCodeSize::get(), Error::<T>::CodeTooLarge);
-			let mut ctx = ExecutionContext::<T, PrefabWasmModule<T>>::top_level(origin, &schedule);
-			let result = ctx.instantiate(endowment, &mut gas_meter, executable, data, &salt)
-				.map(|(_address, output)| output);
-			gas_meter.into_dispatch_result(
-				result,
-				T::WeightInfo::instantiate_with_code(code_len / 1024, salt.len() as u32 / 1024)
-			)
+			Self::instantiate_with_output(
+				origin, endowment, gas_limit, Code::Upload(Bytes(code)), data, salt,
+			).0
 		}
 
 		/// Instantiates a contract from a previously deployed wasm binary.
@@ -393,19 +421,11 @@ pub mod pallet {
 			data: Vec<u8>,
 			salt: Vec<u8>,
 		) -> DispatchResultWithPostInfo {
-			Self::is_root(origin.clone())?;
+			Self::ensure_deployer(origin.clone())?;
 			let origin = ensure_signed(origin)?;
-			let mut gas_meter = GasMeter::new(gas_limit);
-			let schedule = <CurrentSchedule<T>>::get();
-			let executable = PrefabWasmModule::from_storage(code_hash, &schedule, &mut gas_meter)?;
-			let mut ctx = ExecutionContext::<T, PrefabWasmModule<T>>::top_level(origin, &schedule);
-			let code_len = executable.code_len();
-			let result = ctx.instantiate(endowment, &mut gas_meter, executable, data, &salt)
-				.map(|(_address, output)| output);
-			gas_meter.into_dispatch_result(
-				result,
-				T::WeightInfo::instantiate(code_len / 1024, salt.len() as u32 / 1024),
-			)
+			Self::instantiate_with_output(
+				origin, endowment, gas_limit, Code::Existing(code_hash), data, salt,
+			).0
 		}
 
 		/// Allows block producers to claim a small reward for evicting a contract. If a block
@@ -416,13 +436,19 @@ pub mod pallet {
 		/// it was alive.
 		///
 		/// If contract is not evicted as a result of this call, [`Error::ContractNotEvictable`]
-		/// is returned and the sender is not eligible for the reward.
+		/// is returned and the sender is not eligible for the reward. If rent is disabled by
+		/// [`Config::RentFree`], [`Error::RentDisabled`] is returned.
 		#[pallet::weight(T::WeightInfo::claim_surcharge(T::MaxCodeSize::get() / 1024))]
 		pub fn claim_surcharge(
 			origin: OriginFor<T>,
 			dest: T::AccountId,
 			aux_sender: Option<T::AccountId>
 		) -> DispatchResultWithPostInfo {
+			if T::RentFree::get() {
+				return Err(
+					Error::<T>::RentDisabled.with_weight(T::WeightInfo::claim_surcharge(0))
+				);
+			}
 			let origin = origin.into();
 			let (signed, rewarded) = match (origin, aux_sender) {
 				(Ok(frame_system::RawOrigin::Signed(account)), None) => {
@@ -461,6 +487,172 @@ pub mod pallet {
 				)),
 			}
 		}
+
+		/// Stores the supplied `code` for later review without instantiating it.
+		///
+		/// The code is instrumented and stored exactly like [`Self::instantiate_with_code`]
+		/// would do but no contract is created. Contracts can only be instantiated from it
+		/// once it was approved with [`Self::approve_code`].
+		///
+		/// The origin must be an authorized deployer. See [`Self::ensure_deployer`].
+		#[pallet::weight(
+			T::WeightInfo::instrument(code.len() as u32 / 1024)
+				.saturating_add(T::DbWeight::get().reads_writes(1, 3))
+		)]
+		pub fn upload_code(
+			origin: OriginFor<T>,
+			code: Vec<u8>,
+		) -> DispatchResultWithPostInfo {
+			Self::ensure_deployer(origin.clone())?;
+			let origin = ensure_signed(origin)?;
+			ensure!(code.len() as u32 <= T::MaxCodeSize::get(), Error::<T>::CodeTooLarge);
+			let schedule = <CurrentSchedule<T>>::get();
+			let executable = PrefabWasmModule::from_code(code, &schedule)?;
+			ensure!(executable.code_len() <= T::MaxCodeSize::get(), Error::<T>::CodeTooLarge);
+			let code_hash = *executable.code_hash();
+			ensure!(!<CodeStorage<T>>::contains_key(&code_hash), Error::<T>::CodeAlreadyStored);
+			<PendingCode<T>>::insert(&code_hash, origin);
+			executable.store_unused();
+			Ok(().into())
+		}
+
+		/// Approves uploaded code so that contracts can be instantiated from it.
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn approve_code(
+			origin: OriginFor<T>,
+			code_hash: CodeHash<T>,
+		) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			ensure!(<PendingCode<T>>::contains_key(&code_hash), Error::<T>::CodeNotPending);
+			<PendingCode<T>>::remove(&code_hash);
+			Self::deposit_event(Event::CodeApproved(code_hash));
+			Ok(().into())
+		}
+
+		/// Rejects uploaded code and removes it from storage.
+		#[pallet::weight(T::DbWeight::get().reads_writes(2, 3))]
+		pub fn reject_code(
+			origin: OriginFor<T>,
+			code_hash: CodeHash<T>,
+		) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			ensure!(<PendingCode<T>>::contains_key(&code_hash), Error::<T>::CodeNotPending);
+			PrefabWasmModule::<T>::remove_unused(code_hash)?;
+			<PendingCode<T>>::remove(&code_hash);
+			Self::deposit_event(Event::CodeRejected(code_hash));
+			Ok(().into())
+		}
+
+		/// Allows `who` to deploy contracts and update the schedule.
+		///
+		/// If `expiry` is supplied the permission lapses once the chain reaches that block.
+		/// Granting the permission to an account that already holds it replaces its expiry.
+		///
+		/// Only the root origin can grant this permission.
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn add_deployer(
+			origin: OriginFor<T>,
+			who: T::AccountId,
+			expiry: Option<T::BlockNumber>,
+		) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			<Deployers<T>>::insert(&who, expiry);
+			Self::deposit_event(Event::DeployerAdded(who, expiry));
+			Ok(().into())
+		}
+
+		/// Revokes the deployment permission of `who`.
+		///
+		/// Only the root origin can revoke this permission.
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn remove_deployer(
+			origin: OriginFor<T>,
+			who: T::AccountId,
+		) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			ensure!(<Deployers<T>>::contains_key(&who), Error::<T>::NotAuthorizedDeployer);
+			<Deployers<T>>::remove(&who);
+			Self::deposit_event(Event::DeployerRemoved(who));
+			Ok(().into())
+		}
+
+		/// Stops all calls to the contract at `dest`, including calls from other contracts.
+		///
+		/// Only the root origin can pause a contract.
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn pause_contract(
+			origin: OriginFor<T>,
+			dest: T::AccountId,
+		) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			<PausedContracts<T>>::insert(&dest, ());
+			Self::deposit_event(Event::ContractPaused(dest));
+			Ok(().into())
+		}
+
+		/// Allows calls to a contract paused by [`Self::pause_contract`] again.
+		///
+		/// Only the root origin can unpause a contract.
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn unpause_contract(
+			origin: OriginFor<T>,
+			dest: T::AccountId,
+		) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			ensure!(<PausedContracts<T>>::contains_key(&dest), Error::<T>::ContractNotPaused);
+			<PausedContracts<T>>::remove(&dest);
+			Self::deposit_event(Event::ContractUnpaused(dest));
+			Ok(().into())
+		}
+
+		/// Halts or resumes the execution of all contracts.
+		///
+		/// While halted every call and instantiation fails with [`Error::ContractPaused`].
+		/// Only the root origin can flip this switch.
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn set_halted(
+			origin: OriginFor<T>,
+			halted: bool,
+		) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			<Halted<T>>::put(halted);
+			if halted {
+				Self::deposit_event(Event::ExecutionHalted);
+			} else {
+				Self::deposit_event(Event::ExecutionResumed);
+			}
+			Ok(().into())
+		}
+
+		/// Sets the storage limit of all contracts that have no limit of their own.
+		///
+		/// `None` removes the default limit. Only the root origin can set it.
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn set_default_storage_limit(
+			origin: OriginFor<T>,
+			limit: Option<StorageLimit>,
+		) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			<DefaultStorageLimit<T>>::set(limit);
+			Self::deposit_event(Event::DefaultStorageLimitSet(limit));
+			Ok(().into())
+		}
+
+		/// Sets the storage limit of the contract at `dest`, overriding the default limit.
+		///
+		/// `None` removes the limit of the contract so that the default limit applies again.
+		/// Only the root origin can set it.
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn set_storage_limit(
+			origin: OriginFor<T>,
+			dest: T::AccountId,
+			limit: Option<StorageLimit>,
+		) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			<StorageLimits<T>>::set(&dest, limit);
+			Self::deposit_event(Event::StorageLimitSet(dest, limit));
+			Ok(().into())
+		}
 	}
 
 	impl<T: Config> Pallet<T>
@@ -468,26 +660,65 @@ pub mod pallet {
 		T::AccountId: UncheckedFrom<T::Hash>,
 		T::AccountId: AsRef<[u8]>,
 	{
-		fn is_root(origin: OriginFor<T>) -> Result<(), DispatchError>{
-			let sudo_key = pallet_sudo::Pallet::<T>::key();
-			let test_origin = match origin.into() {
-				Ok(RawOrigin::Signed(account_id)) => {
-					let mut result = Ok(());
-					if sudo_key != account_id{
-						result = Err(DispatchError::BadOrigin);
-					}
-					result
-				}
+		/// Checks whether `origin` may deploy contracts and update the schedule.
+		///
+		/// This is the case for the root origin, the sudo key and every account in
+		/// [`Deployers`] whose permission has not yet expired.
+		pub fn ensure_deployer(origin: OriginFor<T>) -> DispatchResult {
+			match origin.into() {
 				Ok(RawOrigin::Root) => Ok(()),
+				Ok(RawOrigin::Signed(who)) => {
+					ensure!(Self::is_deployer(&who), Error::<T>::NotAuthorizedDeployer);
+					Ok(())
+				}
 				_ => Err(DispatchError::BadOrigin),
-			};
-			test_origin
-		}	
+			}
+		}
+
+		/// Returns whether `who` is currently allowed to deploy contracts.
+		pub fn is_deployer(who: &T::AccountId) -> bool {
+			if *who == pallet_sudo::Pallet::<T>::key() {
+				return true;
+			}
+			match <Deployers<T>>::get(who) {
+				Some(None) => true,
+				Some(Some(expiry)) => <frame_system::Pallet<T>>::block_number() < expiry,
+				None => false,
+			}
+		}
+
+		/// Returns whether calls to `dest` are rejected because it or all contracts are paused.
+		pub fn is_paused(dest: &T::AccountId) -> bool {
+			Self::is_halted() || <PausedContracts<T>>::contains_key(dest)
+		}
+
+		/// Returns whether the execution of all contracts is halted.
+		pub fn is_halted() -> bool {
+			<Halted<T>>::get()
+		}
+
+		/// Returns the storage limit the contract at `address` is subject to.
+		pub fn storage_limit(address: &T::AccountId) -> Option<StorageLimit> {
+			<StorageLimits<T>>::get(address).or_else(<DefaultStorageLimit<T>>::get)
+		}
+
+		/// Returns the storage occupied by the alive contract at `address` and its limit.
+		pub fn storage_usage(address: &T::AccountId) -> Option<StorageUsage> {
+			let contract = ContractInfoOf::<T>::get(address)?.get_alive()?;
+			Some(StorageUsage {
+				bytes: contract.storage_size,
+				items: contract.pair_count,
+				limit: Self::storage_limit(address),
+			})
+		}
 	}
 
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
-	#[pallet::metadata(T::AccountId = "AccountId", T::Hash = "Hash", BalanceOf<T> = "Balance")]
+	#[pallet::metadata(
+		T::AccountId = "AccountId", T::Hash = "Hash", BalanceOf<T> = "Balance",
+		T::BlockNumber = "BlockNumber",
+	)]
 	pub enum Event<T: Config> {
 		/// Contract deployed by address at the specified address. \[deployer, contract\]
 		Instantiated(T::AccountId, T::AccountId),
@@ -523,6 +754,12 @@ pub mod pallet {
 		/// Code with the specified hash has been stored. \[code_hash\]
 		CodeStored(T::Hash),
 
+		/// Uploaded code was approved for instantiation. \[code_hash\]
+		CodeApproved(T::Hash),
+
+		/// Uploaded code was rejected and removed from storage. \[code_hash\]
+		CodeRejected(T::Hash),
+
 		/// Triggered when the current schedule is updated.
 		/// \[version\]
 		///
@@ -546,6 +783,39 @@ pub mod pallet {
 		///
 		/// This happens when the last contract that uses this code hash was removed or evicted.
 		CodeRemoved(T::Hash),
+
+		/// An account was allowed to deploy contracts.
+		/// \[deployer, expiry\]
+		///
+		/// # Params
+		///
+		/// - `deployer`: The account that received the permission.
+		/// - `expiry`: The block at which the permission lapses, if any.
+		DeployerAdded(T::AccountId, Option<T::BlockNumber>),
+
+		/// The deployment permission of an account was revoked. \[deployer\]
+		DeployerRemoved(T::AccountId),
+
+		/// Calls to a contract are rejected until it is unpaused. \[contract\]
+		ContractPaused(T::AccountId),
+
+		/// Calls to a contract are accepted again. \[contract\]
+		ContractUnpaused(T::AccountId),
+
+		/// The execution of all contracts was halted.
+		ExecutionHalted,
+
+		/// The execution of contracts was resumed.
+		ExecutionResumed,
+
+		/// Metadata was attached to the code with the specified hash. \[code_hash\]
+		MetadataStored(T::Hash),
+
+		/// The default storage limit of contracts was set or removed. \[limit\]
+		DefaultStorageLimitSet(Option<StorageLimit>),
+
+		/// The storage limit of a contract was set or removed. \[contract, limit\]
+		StorageLimitSet(T::AccountId, Option<StorageLimit>),
 	}
 
 	#[pallet::error]
@@ -627,11 +897,34 @@ pub mod pallet {
 		/// A storage modification exhausted the 32bit type that holds the storage size.
 		///
 		/// This can either happen when the accumulated storage in bytes is too large or
-		/// when number of storage items is too large.
+		/// when number of storage items is too large. It is also returned when a write
+		/// would exceed the [`StorageLimit`] of the contract.
 		StorageExhausted,
 		/// A contract with the same AccountId already exists.
 		DuplicateContract,
-		NotRootOrigin,
+		/// The origin is neither root, the sudo key nor an account with an unexpired
+		/// entry in the deployer allowlist.
+		NotAuthorizedDeployer,
+		/// The code was uploaded with [`Pallet::upload_code`] but has not been approved yet.
+		CodeNotApproved,
+		/// The code hash is not awaiting approval.
+		CodeNotPending,
+		/// The code supplied to [`Pallet::upload_code`] is already stored.
+		CodeAlreadyStored,
+		/// The code cannot be removed because contracts are using it.
+		CodeInUse,
+		/// The contract or the execution of all contracts is paused.
+		ContractPaused,
+		/// The contract to unpause is not paused.
+		ContractNotPaused,
+		/// A new schedule must not lower any of the limits of the current one.
+		ScheduleLimitsLowered,
+		/// The metadata is longer than `MaxMetadataLen`.
+		MetadataTooLarge,
+		/// Rent, eviction and restoration are disabled by `RentFree`.
+		RentDisabled,
+		/// The message passed to `seal_debug_message` is not valid UTF-8.
+		DebugMessageInvalidUTF8,
 	}
 
 	/// Current cost schedule for contracts.
@@ -642,6 +935,13 @@ pub mod pallet {
 	#[pallet::storage]
 	pub(crate) type PristineCode<T: Config> = StorageMap<_, Identity, CodeHash<T>, Vec<u8>>;
 
+	/// The (usually compressed) ink! metadata of stored code, needed to decode the messages and
+	/// events of contracts using it.
+	///
+	/// Removed together with the [`PristineCode`].
+	#[pallet::storage]
+	pub(crate) type CodeMetadata<T: Config> = StorageMap<_, Identity, CodeHash<T>, Vec<u8>>;
+
 	/// A mapping between an original code hash and instrumented wasm code, ready for execution.
 	#[pallet::storage]
 	pub(crate) type CodeStorage<T: Config> = StorageMap<_, Identity, CodeHash<T>, PrefabWasmModule<T>>;
@@ -663,6 +963,40 @@ pub mod pallet {
 	#[pallet::storage]
 	pub(crate) type DeletionQueue<T: Config> = StorageValue<_, Vec<DeletedContract>, ValueQuery>;
 
+	/// Code uploaded by [`Pallet::upload_code`] that awaits approval, with its uploader.
+	///
+	/// Contracts cannot be instantiated from a code hash contained in this map.
+	#[pallet::storage]
+	pub(crate) type PendingCode<T: Config> = StorageMap<_, Identity, CodeHash<T>, T::AccountId>;
+
+	/// Accounts allowed to deploy contracts and update the schedule in addition to the sudo key.
+	///
+	/// The value is the block at which the permission lapses or `None` if it never does.
+	#[pallet::storage]
+	pub(crate) type Deployers<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, Option<T::BlockNumber>>;
+
+	/// Contracts that cannot be called until they are unpaused.
+	///
+	/// TWOX-NOTE: SAFE since `AccountId` is a secure hash.
+	#[pallet::storage]
+	pub(crate) type PausedContracts<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, ()>;
+
+	/// Whether the execution of all contracts is halted.
+	#[pallet::storage]
+	pub(crate) type Halted<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	/// The storage limit of contracts without a limit in [`StorageLimits`].
+	#[pallet::storage]
+	pub(crate) type DefaultStorageLimit<T: Config> = StorageValue<_, StorageLimit>;
+
+	/// The storage limits of single contracts, overriding the [`DefaultStorageLimit`].
+	///
+	/// TWOX-NOTE: SAFE since `AccountId` is a secure hash.
+	#[pallet::storage]
+	pub(crate) type StorageLimits<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, StorageLimit>;
+
 
 	#[pallet::genesis_config]
 	pub struct GenesisConfig<T: Config> {
@@ -687,33 +1021,159 @@ pub mod pallet {
 	}
 }
 
+/// The maximum number of keys or entries returned by [`Pallet::storage_keys`] and
+/// [`Pallet::storage_entries`], whatever `limit` the caller asks for.
+pub const MAX_STORAGE_PAGE_LEN: u32 = 1000;
+
 impl<T: Config> Pallet<T>
 where
 	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>,
 {
+	/// Perform a call like the [`Self::call`] dispatchable and also return the output of the
+	/// contract.
+	///
+	/// This allows pallets wrapping this one to report what the contract returned. The output
+	/// is `None` if the call did not complete.
+	pub fn call_with_output(
+		origin: T::AccountId,
+		dest: T::AccountId,
+		value: BalanceOf<T>,
+		gas_limit: Weight,
+		input_data: Vec<u8>,
+	) -> (DispatchResultWithPostInfo, Option<ExecReturnValue>) {
+		let mut gas_meter = GasMeter::new(gas_limit);
+		let schedule = <CurrentSchedule<T>>::get();
+		let tracer = RefCell::new(Tracer::default());
+		let mut ctx = ExecutionContext::<T, PrefabWasmModule<T>>::top_level(origin, &schedule);
+		if trace::is_recording() {
+			ctx = ctx.with_tracer(&tracer);
+		}
+		let (result, output, code_len) = match ctx.call(dest, value, &mut gas_meter, input_data) {
+			Ok((output, len)) => (Ok(()), Some(output), len),
+			Err((err, len)) => (Err(err), None, len),
+		};
+		trace::record(tracer.into_inner());
+		(gas_meter.into_dispatch_result(result, T::WeightInfo::call(code_len / 1024)), output)
+	}
+
+	/// Instantiate a contract like the [`Self::instantiate_with_code`] or [`Self::instantiate`]
+	/// dispatchable, depending on `code`, and also return the address and output of the new
+	/// contract.
+	///
+	/// The caller is responsible for checking that `origin` is an authorized deployer.
+	pub fn instantiate_with_output(
+		origin: T::AccountId,
+		endowment: BalanceOf<T>,
+		gas_limit: Weight,
+		code: Code<CodeHash<T>>,
+		data: Vec<u8>,
+		salt: Vec<u8>,
+	) -> (DispatchResultWithPostInfo, Option<(T::AccountId, ExecReturnValue)>) {
+		let mut gas_meter = GasMeter::new(gas_limit);
+		let schedule = <CurrentSchedule<T>>::get();
+		let salt_len = salt.len() as u32;
+		let executable = match code {
+			Code::Upload(Bytes(binary)) => {
+				if binary.len() as u32 > T::MaxCodeSize::get() {
+					return (Err(Error::<T>::CodeTooLarge.into()), None);
+				}
+				PrefabWasmModule::from_code(binary, &schedule).and_then(|executable| {
+					if executable.code_len() > T::MaxCodeSize::get() {
+						return Err(Error::<T>::CodeTooLarge.into());
+					}
+					Ok((executable, true))
+				})
+			},
+			Code::Existing(hash) => PrefabWasmModule::from_storage(hash, &schedule, &mut gas_meter)
+				.map(|executable| (executable, false)),
+		};
+		let (executable, uploaded) = match executable {
+			Ok(executable) => executable,
+			Err(error) => return (Err(error.into()), None),
+		};
+		let code_len = executable.code_len();
+		let base_weight = if uploaded {
+			T::WeightInfo::instantiate_with_code(code_len / 1024, salt_len / 1024)
+		} else {
+			T::WeightInfo::instantiate(code_len / 1024, salt_len / 1024)
+		};
+		let tracer = RefCell::new(Tracer::default());
+		let mut ctx = ExecutionContext::<T, PrefabWasmModule<T>>::top_level(origin, &schedule);
+		if trace::is_recording() {
+			ctx = ctx.with_tracer(&tracer);
+		}
+		let (result, output) = match ctx.instantiate(endowment, &mut gas_meter, executable, data, &salt) {
+			Ok(output) => (Ok(()), Some(output)),
+			Err(err) => (Err(err), None),
+		};
+		trace::record(tracer.into_inner());
+		(gas_meter.into_dispatch_result(result, base_weight), output)
+	}
+
+	/// Replace the code of the alive contract at `dest` with the already stored `code_hash`.
+	///
+	/// The storage of the contract is left untouched. The refcount of the new code is
+	/// incremented and the one of the old code decremented, removing the old code if this was
+	/// its last user. Returns the previous code hash.
+	///
+	/// The caller is responsible for checking that the origin may upgrade `dest`.
+	pub fn set_contract_code(
+		dest: &T::AccountId,
+		code_hash: CodeHash<T>,
+	) -> Result<CodeHash<T>, DispatchError> {
+		if <PendingCode<T>>::contains_key(&code_hash) {
+			return Err(Error::<T>::CodeNotApproved.into());
+		}
+		<ContractInfoOf<T>>::try_mutate(dest, |info| {
+			let contract = match info {
+				Some(ContractInfo::Alive(contract)) => contract,
+				_ => return Err(Error::<T>::NotCallable.into()),
+			};
+			let old_code_hash = contract.code_hash;
+			// Increment first so that the code is not removed when it is replaced by itself.
+			PrefabWasmModule::<T>::add_user(code_hash)?;
+			PrefabWasmModule::<T>::remove_user(old_code_hash);
+			contract.code_hash = code_hash;
+			Ok(old_code_hash)
+		})
+	}
+
 	/// Perform a call to a specified contract.
 	///
 	/// This function is similar to [`Self::call`], but doesn't perform any address lookups
 	/// and better suitable for calling directly from Rust.
 	///
 	/// It returns the execution result and the amount of used weight.
+	///
+	/// If `debug` is set to `true` the messages passed to `seal_debug_message` are collected
+	/// and returned in the `debug_message` field of the result. This must never be set for
+	/// on-chain execution.
 	pub fn bare_call(
 		origin: T::AccountId,
 		dest: T::AccountId,
 		value: BalanceOf<T>,
 		gas_limit: Weight,
 		input_data: Vec<u8>,
+		debug: bool,
 	) -> ContractExecResult {
-		let mut gas_meter = GasMeter::new(gas_limit);
-		let schedule = <CurrentSchedule<T>>::get();
-		let mut ctx = ExecutionContext::<T, PrefabWasmModule<T>>::top_level(origin, &schedule);
-		let result = ctx.call(dest, value, &mut gas_meter, input_data);
-		let gas_consumed = gas_meter.gas_spent();
-		ContractExecResult {
-			result: result.map(|r| r.0).map_err(|r| r.0.error),
-			gas_consumed,
-			debug_message: Bytes(Vec::new()),
-		}
+		Self::internal_call(origin, dest, value, gas_limit, input_data, debug, None)
+	}
+
+	/// Perform a call like [`Self::bare_call`] with `debug` set and record its call tree.
+	///
+	/// Next to the result it returns a [`CallFrame`] for every call and instantiation that
+	/// happened during the execution, in the order in which they were entered.
+	pub fn bare_call_traced(
+		origin: T::AccountId,
+		dest: T::AccountId,
+		value: BalanceOf<T>,
+		gas_limit: Weight,
+		input_data: Vec<u8>,
+	) -> (ContractExecResult, Vec<CallFrameOf<T>>) {
+		let tracer = RefCell::new(Tracer::default());
+		let result =
+			Self::internal_call(origin, dest, value, gas_limit, input_data, true, Some(&tracer));
+		(result, tracer.into_inner().into_frames())
 	}
 
 	/// Instantiate a new contract.
@@ -726,6 +1186,11 @@ where
 	/// If `compute_projection` is set to `true` the result also contains the rent projection.
 	/// This is optional because some non trivial and stateful work is performed to compute
 	/// the projection. See [`Self::rent_projection`].
+	///
+	/// Like the dispatchables, it fails with [`Error::NotAuthorizedDeployer`] if `origin` is
+	/// not an authorized deployer, so that dry runs report what a real instantiation would do.
+	///
+	/// `debug` has the same meaning as in [`Self::bare_call`].
 	pub fn bare_instantiate(
 		origin: T::AccountId,
 		endowment: BalanceOf<T>,
@@ -734,12 +1199,95 @@ where
 		data: Vec<u8>,
 		salt: Vec<u8>,
 		compute_projection: bool,
+		debug: bool,
+	) -> ContractInstantiateResult<T::AccountId, T::BlockNumber> {
+		Self::internal_instantiate(
+			origin, endowment, gas_limit, code, data, salt, compute_projection, debug, None,
+		)
+	}
+
+	/// Instantiate a contract like [`Self::bare_instantiate`] with `debug` set and record its
+	/// call tree. See [`Self::bare_call_traced`].
+	pub fn bare_instantiate_traced(
+		origin: T::AccountId,
+		endowment: BalanceOf<T>,
+		gas_limit: Weight,
+		code: Code<CodeHash<T>>,
+		data: Vec<u8>,
+		salt: Vec<u8>,
+		compute_projection: bool,
+	) -> (ContractInstantiateResult<T::AccountId, T::BlockNumber>, Vec<CallFrameOf<T>>) {
+		let tracer = RefCell::new(Tracer::default());
+		let result = Self::internal_instantiate(
+			origin, endowment, gas_limit, code, data, salt, compute_projection, true, Some(&tracer),
+		);
+		(result, tracer.into_inner().into_frames())
+	}
+
+	fn internal_call(
+		origin: T::AccountId,
+		dest: T::AccountId,
+		value: BalanceOf<T>,
+		gas_limit: Weight,
+		input_data: Vec<u8>,
+		debug: bool,
+		tracer: Option<&RefCell<Tracer<T>>>,
+	) -> ContractExecResult {
+		let mut gas_meter = GasMeter::new(gas_limit);
+		let schedule = <CurrentSchedule<T>>::get();
+		let debug_buffer = RefCell::new(Vec::new());
+		let mut ctx = ExecutionContext::<T, PrefabWasmModule<T>>::top_level(origin, &schedule);
+		if debug {
+			ctx = ctx.with_debug_buffer(&debug_buffer);
+		}
+		if let Some(tracer) = tracer {
+			ctx = ctx.with_tracer(tracer);
+		}
+		let result = ctx.call(dest, value, &mut gas_meter, input_data);
+		let gas_consumed = gas_meter.gas_spent();
+		ContractExecResult {
+			result: result.map(|r| r.0).map_err(|r| r.0.error),
+			gas_consumed,
+			debug_message: Bytes(debug_buffer.into_inner()),
+		}
+	}
+
+	fn internal_instantiate(
+		origin: T::AccountId,
+		endowment: BalanceOf<T>,
+		gas_limit: Weight,
+		code: Code<CodeHash<T>>,
+		data: Vec<u8>,
+		salt: Vec<u8>,
+		compute_projection: bool,
+		debug: bool,
+		tracer: Option<&RefCell<Tracer<T>>>,
 	) -> ContractInstantiateResult<T::AccountId, T::BlockNumber> {
 		let mut gas_meter = GasMeter::new(gas_limit);
+		if !Self::is_deployer(&origin) {
+			return ContractInstantiateResult {
+				result: Err(Error::<T>::NotAuthorizedDeployer.into()),
+				gas_consumed: gas_meter.gas_spent(),
+				debug_message: Bytes(Vec::new()),
+			};
+		}
 		let schedule = <CurrentSchedule<T>>::get();
+		let debug_buffer = RefCell::new(Vec::new());
 		let mut ctx = ExecutionContext::<T, PrefabWasmModule<T>>::top_level(origin, &schedule);
+		if debug {
+			ctx = ctx.with_debug_buffer(&debug_buffer);
+		}
+		if let Some(tracer) = tracer {
+			ctx = ctx.with_tracer(tracer);
+		}
 		let executable = match code {
-			Code::Upload(Bytes(binary)) => PrefabWasmModule::from_code(binary, &schedule),
+			Code::Upload(Bytes(binary)) => {
+				if binary.len() as u32 > T::MaxCodeSize::get() {
+					Err(Error::<T>::CodeTooLarge.into())
+				} else {
+					PrefabWasmModule::from_code(binary, &schedule)
+				}
+			},
 			Code::Existing(hash) => PrefabWasmModule::from_storage(hash, &schedule, &mut gas_meter),
 		};
 		let executable = match executable {
@@ -768,19 +1316,112 @@ where
 		ContractInstantiateResult {
 			result: result.map_err(|e| e.error),
 			gas_consumed: gas_meter.gas_spent(),
-			debug_message: Bytes(Vec::new()),
+			debug_message: Bytes(debug_buffer.into_inner()),
 		}
 	}
 
 	/// Query storage of a specified contract under a specified key.
 	pub fn get_storage(address: T::AccountId, key: [u8; 32]) -> GetStorageResult {
-		let contract_info = ContractInfoOf::<T>::get(&address)
+		let contract_info = Self::alive_contract_info(&address)?;
+		let maybe_value = Storage::<T>::read(&contract_info.trie_id, &key);
+		Ok(maybe_value)
+	}
+
+	/// Query storage of a specified contract under each of the specified keys.
+	///
+	/// The values are returned in the order of `keys`. Only the first
+	/// [`MAX_STORAGE_PAGE_LEN`] keys are read, the rest is ignored.
+	pub fn get_storage_many(
+		address: T::AccountId,
+		keys: Vec<[u8; 32]>,
+	) -> Result<Vec<Option<Vec<u8>>>, ContractAccessError> {
+		let contract_info = Self::alive_contract_info(&address)?;
+		Ok(keys.iter()
+			.take(MAX_STORAGE_PAGE_LEN as usize)
+			.map(|key| Storage::<T>::read(&contract_info.trie_id, key))
+			.collect())
+	}
+
+	/// List up to `limit` keys of the child trie of a specified contract which start with
+	/// `prefix`, starting after `start_key` if supplied. `limit` is capped at
+	/// [`MAX_STORAGE_PAGE_LEN`].
+	///
+	/// The keys are the blake2_256 hashes of the keys used by the contract, as stored in the
+	/// trie given by `AliveContractInfo::child_trie_info`. Meant for RPC use only.
+	pub fn storage_keys(
+		address: T::AccountId,
+		prefix: Vec<u8>,
+		start_key: Option<Vec<u8>>,
+		limit: u32,
+	) -> Result<Vec<Vec<u8>>, ContractAccessError> {
+		let contract_info = Self::alive_contract_info(&address)?;
+		let limit = limit.min(MAX_STORAGE_PAGE_LEN);
+		Ok(Storage::<T>::keys(&contract_info.trie_id, &prefix, start_key.as_deref(), limit))
+	}
+
+	/// List up to `limit` key-value pairs of the child trie of a specified contract, starting
+	/// after `start_key` if supplied. `limit` is capped at [`MAX_STORAGE_PAGE_LEN`].
+	///
+	/// The keys are hashed like those returned by [`Self::storage_keys`]. Meant for RPC use only.
+	pub fn storage_entries(
+		address: T::AccountId,
+		start_key: Option<Vec<u8>>,
+		limit: u32,
+	) -> Result<Vec<(Vec<u8>, Vec<u8>)>, ContractAccessError> {
+		let contract_info = Self::alive_contract_info(&address)?;
+		let limit = limit.min(MAX_STORAGE_PAGE_LEN);
+		Ok(Storage::<T>::entries(&contract_info.trie_id, start_key.as_deref(), limit))
+	}
+
+	/// Returns the key of the `ContractInfoOf` entry of the contract at `address` together
+	/// with the id of its child trie.
+	///
+	/// These locate the storage of the contract in the state and are needed to prove the
+	/// values it stores.
+	pub fn storage_proof_keys(
+		address: T::AccountId,
+	) -> Result<(Vec<u8>, Vec<u8>), ContractAccessError> {
+		let contract_info = Self::alive_contract_info(&address)?;
+		Ok((ContractInfoOf::<T>::hashed_key_for(&address), contract_info.trie_id))
+	}
+
+	fn alive_contract_info(
+		address: &T::AccountId,
+	) -> Result<AliveContractInfo<T>, ContractAccessError> {
+		ContractInfoOf::<T>::get(address)
 			.ok_or(ContractAccessError::DoesntExist)?
 			.get_alive()
-			.ok_or(ContractAccessError::IsTombstone)?;
+			.ok_or(ContractAccessError::IsTombstone)
+	}
 
-		let maybe_value = Storage::<T>::read(&contract_info.trie_id, &key);
-		Ok(maybe_value)
+	/// Attaches `metadata` to the stored code `code_hash` unless it already has metadata.
+	///
+	/// Metadata is tied to the code and cannot be replaced. Returns whether `metadata` was
+	/// attached. The caller is responsible for checking that the origin may attach metadata.
+	pub fn attach_metadata(
+		code_hash: CodeHash<T>,
+		metadata: Vec<u8>,
+	) -> Result<bool, DispatchError> {
+		ensure!(metadata.len() as u32 <= T::MaxMetadataLen::get(), Error::<T>::MetadataTooLarge);
+		ensure!(<CodeStorage<T>>::contains_key(&code_hash), Error::<T>::CodeNotFound);
+		if <CodeMetadata<T>>::contains_key(&code_hash) {
+			return Ok(false);
+		}
+		<CodeMetadata<T>>::insert(&code_hash, metadata);
+		Self::deposit_event(Event::MetadataStored(code_hash));
+		Ok(true)
+	}
+
+	/// Returns the metadata attached to the code `code_hash`.
+	pub fn code_metadata(code_hash: &CodeHash<T>) -> Option<Vec<u8>> {
+		<CodeMetadata<T>>::get(code_hash)
+	}
+
+	/// Returns the code hash of the alive contract at `address`.
+	pub fn code_hash(address: &T::AccountId) -> Option<CodeHash<T>> {
+		ContractInfoOf::<T>::get(address)
+			.and_then(|contract_info| contract_info.get_alive())
+			.map(|contract_info| contract_info.code_hash)
 	}
 
 	/// Query how many blocks the contract stays alive given that the amount endowment
@@ -15,13 +15,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{Config, Weight, CurrentSchedule, Pallet, Schedule};
+use crate::{Config, ContractInfo, ContractInfoOf, Weight, CurrentSchedule, Pallet, Schedule};
 use frame_support::traits::{GetPalletVersion, PalletVersion, Get};
+use sp_std::prelude::*;
 
 pub fn migrate<T: Config>() -> Weight {
 	let mut weight: Weight = 0;
+	let version = <Pallet<T>>::storage_version();
 
-	match <Pallet<T>>::storage_version() {
+	match version {
 		// Replace the schedule with the new default and increment its version.
 		Some(version) if version == PalletVersion::new(3, 0, 0) => {
 			weight = weight.saturating_add(T::DbWeight::get().reads_writes(1, 1));
@@ -37,5 +39,39 @@ pub fn migrate<T: Config>() -> Weight {
 		_ => (),
 	}
 
+	match version {
+		// Remove the tombstones left from before rent could be disabled.
+		Some(version) if version < PalletVersion::new(3, 1, 0) => {
+			weight = weight.saturating_add(remove_tombstones::<T>());
+		}
+		_ => (),
+	}
+
 	weight
 }
+
+/// Removes all tombstones if rent is disabled by [`Config::RentFree`].
+///
+/// A tombstone can only be revived through `seal_restore_to`, which is disabled together with
+/// rent. The storage of an evicted contract is queued for deletion on eviction and its code
+/// is dropped, so only the `ContractInfoOf` entry is left to remove. This iterates all
+/// contracts and therefore only runs once, on the upgrade from a version before `3.1.0`.
+fn remove_tombstones<T: Config>() -> Weight {
+	if !T::RentFree::get() {
+		return 0;
+	}
+	let mut contracts: Weight = 0;
+	let tombstones = <ContractInfoOf<T>>::iter()
+		.filter_map(|(account, info)| {
+			contracts = contracts.saturating_add(1);
+			match info {
+				ContractInfo::Tombstone(_) => Some(account),
+				ContractInfo::Alive(_) => None,
+			}
+		})
+		.collect::<Vec<_>>();
+	for account in &tombstones {
+		<ContractInfoOf<T>>::remove(account);
+	}
+	T::DbWeight::get().reads_writes(contracts, tombstones.len() as Weight)
+}
@@ -300,11 +300,16 @@ where
 	/// This functions does **not** evict the contract. It returns `None` in case the
 	/// contract is in need of eviction. [`try_eviction`] must
 	/// be called to perform the eviction.
+	///
+	/// Does nothing if rent is disabled by [`Config::RentFree`].
 	pub fn charge(
 		account: &T::AccountId,
 		contract: AliveContractInfo<T>,
 		code_size: u32,
 	) -> Result<Option<AliveContractInfo<T>>, DispatchError> {
+		if T::RentFree::get() {
+			return Ok(Some(contract));
+		}
 		let current_block_number = <frame_system::Pallet<T>>::block_number();
 		let verdict = Self::consider_case(
 			account,
@@ -378,6 +383,8 @@ where
 	/// NOTE that this is not a side-effect free function! It will actually collect rent and then
 	/// compute the projection. This function is only used for implementation of an RPC method through
 	/// `RuntimeApi` meaning that the changes will be discarded anyway.
+	///
+	/// Alive contracts are never evicted if rent is disabled by [`Config::RentFree`].
 	pub fn compute_projection(
 		account: &T::AccountId,
 	) -> RentProjectionResult<T::BlockNumber> {
@@ -388,6 +395,9 @@ where
 			None | Some(ContractInfo::Tombstone(_)) => return Err(IsTombstone),
 			Some(ContractInfo::Alive(contract)) => contract,
 		};
+		if T::RentFree::get() {
+			return Ok(RentProjection::NoEviction);
+		}
 		let module = <PrefabWasmModule<T>>::from_storage_noinstr(alive_contract_info.code_hash)
 			.map_err(|_| IsTombstone)?;
 		let code_size = module.occupied_storage();
@@ -456,6 +466,8 @@ where
 	/// the restored account. The restored account will inherit the last write block and its last
 	/// deduct block will be set to the current block.
 	///
+	/// Fails with [`Error::RentDisabled`] if rent is disabled by [`Config::RentFree`].
+	///
 	/// # Return Value
 	///
 	/// Result<(CallerCodeSize, DestCodeSize), (DispatchError, CallerCodeSize, DestCodesize)>
@@ -466,6 +478,9 @@ where
 		rent_allowance: BalanceOf<T>,
 		delta: Vec<crate::exec::StorageKey>,
 	) -> Result<(u32, u32), (DispatchError, u32, u32)> {
+		if T::RentFree::get() {
+			return Err((Error::<T>::RentDisabled.into(), 0, 0));
+		}
 		let mut origin_contract = <ContractInfoOf<T>>::get(&origin)
 			.and_then(|c| c.get_alive())
 			.ok_or((Error::<T>::InvalidSourceContract.into(), 0, 0))?;
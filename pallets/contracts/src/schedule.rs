@@ -43,7 +43,8 @@ pub const INSTR_BENCHMARK_BATCH_SIZE: u32 = 1_000;
 ///
 /// Its fields are private to the crate in order to allow addition of new contract
 /// callable functions without bumping to a new major version. A genesis config should
-/// rely on public functions of this type.
+/// rely on public functions of this type: start from a [`SchedulePreset`] or
+/// [`Default::default`] and adjust it with the `with_*` builder functions.
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "std", serde(bound(serialize = "", deserialize = "")))]
 #[derive(Clone, Encode, Decode, PartialEq, Eq, ScheduleDebug)]
@@ -128,6 +129,65 @@ impl Limits {
 	pub fn max_memory_size(&self) -> u32 {
 		self.memory_pages * 64 * 1024
 	}
+
+	/// Returns whether none of these limits is lower than the same limit in `other`.
+	///
+	/// Only limits that cover `other` can replace it on a live chain.
+	pub fn covers(&self, other: &Self) -> bool {
+		self.event_topics >= other.event_topics &&
+			self.stack_height >= other.stack_height &&
+			self.globals >= other.globals &&
+			self.parameters >= other.parameters &&
+			self.memory_pages >= other.memory_pages &&
+			self.table_size >= other.table_size &&
+			self.br_table_size >= other.br_table_size &&
+			self.subject_len >= other.subject_len
+	}
+}
+
+/// Named schedules that can be selected by a chain spec.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+pub enum SchedulePreset {
+	/// The [`Default`] schedule.
+	Default,
+	/// The default schedule with `seal_println` enabled. MUST NOT be used on production chains.
+	Development,
+	/// Raised limits for permissioned chains whose deployers are trusted to upload large
+	/// contracts.
+	LargeContracts,
+}
+
+#[cfg(feature = "std")]
+impl std::str::FromStr for SchedulePreset {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"default" => Ok(Self::Default),
+			"dev" | "development" => Ok(Self::Development),
+			"large-contracts" => Ok(Self::LargeContracts),
+			_ => Err(format!("Unknown schedule preset: {}", s)),
+		}
+	}
+}
+
+impl<T: Config> From<SchedulePreset> for Schedule<T> {
+	fn from(preset: SchedulePreset) -> Self {
+		match preset {
+			SchedulePreset::Default => Self::default(),
+			SchedulePreset::Development => Self::default().enable_println(true),
+			SchedulePreset::LargeContracts => Self::default().with_limits(Limits {
+				stack_height: 1024,
+				globals: 512,
+				memory_pages: 64,
+				table_size: 8192,
+				br_table_size: 1024,
+				..Default::default()
+			}),
+		}
+	}
 }
 
 /// Describes the weight for all categories of supported wasm instructions.
@@ -369,6 +429,9 @@ pub struct HostFnWeights<T: Config> {
 	/// Weight of calling `seal_rent_params`.
 	pub rent_params: Weight,
 
+	/// Weight of calling `seal_debug_message`.
+	pub debug_message: Weight,
+
 	/// The type parameter is used in the default implementation.
 	#[codec(skip)]
 	pub _phantom: PhantomData<T>
@@ -595,6 +658,7 @@ impl<T: Config> Default for HostFnWeights<T> {
 			hash_blake2_128: 0,
 			hash_blake2_128_per_byte: 0,
 			rent_params: 0,
+			debug_message: 0,
 			_phantom: PhantomData,
 		}
 	}
@@ -620,6 +684,57 @@ impl<T: Config> Schedule<T> {
 		self
 	}
 
+	/// Set the version of the schedule.
+	///
+	/// The version must be increased whenever the instruction weights change.
+	pub fn with_version(mut self, version: u32) -> Self {
+		self.version = version;
+		self
+	}
+
+	/// Replace the limits of the schedule.
+	///
+	/// # Note
+	///
+	/// [`Pallet::update_schedule`](crate::Pallet::update_schedule) rejects schedules that
+	/// lower any limit of the current schedule.
+	pub fn with_limits(mut self, limits: Limits) -> Self {
+		self.limits = limits;
+		self
+	}
+
+	/// Replace the weights of the wasm instructions.
+	pub fn with_instruction_weights(mut self, weights: InstructionWeights<T>) -> Self {
+		self.instruction_weights = weights;
+		self
+	}
+
+	/// Replace the weights of the functions a contract can import.
+	pub fn with_host_fn_weights(mut self, weights: HostFnWeights<T>) -> Self {
+		self.host_fn_weights = weights;
+		self
+	}
+
+	/// The version of the schedule.
+	pub fn version(&self) -> u32 {
+		self.version
+	}
+
+	/// The upper limits on various metrics.
+	pub fn limits(&self) -> &Limits {
+		&self.limits
+	}
+
+	/// The weights of the wasm instructions.
+	pub fn instruction_weights(&self) -> &InstructionWeights<T> {
+		&self.instruction_weights
+	}
+
+	/// The weights of the functions a contract can import.
+	pub fn host_fn_weights(&self) -> &HostFnWeights<T> {
+		&self.host_fn_weights
+	}
+
 	pub(crate) fn rules(&self, module: &elements::Module) -> impl rules::Rules + '_ {
 		ScheduleRules {
 			schedule: &self,
@@ -722,4 +837,19 @@ mod test {
 		let schedule = Schedule::<Test>::default();
 		println!("{:#?}", schedule);
 	}
+
+	#[test]
+	fn presets_do_not_lower_default_limits() {
+		let default = Limits::default();
+		for preset in &[
+			SchedulePreset::Default,
+			SchedulePreset::Development,
+			SchedulePreset::LargeContracts,
+		] {
+			let schedule = Schedule::<Test>::from(*preset);
+			assert!(schedule.limits().covers(&default));
+		}
+		let large = Schedule::<Test>::from(SchedulePreset::LargeContracts);
+		assert!(!default.covers(large.limits()));
+	}
 }
@@ -24,6 +24,8 @@ use crate::{
 	weights::WeightInfo,
 };
 use codec::{Codec, Encode, Decode};
+#[cfg(feature = "std")]
+use serde::{Serialize, Deserialize};
 use sp_std::prelude::*;
 use sp_std::{marker::PhantomData, fmt::Debug};
 use sp_io::hashing::blake2_256;
@@ -80,6 +82,34 @@ impl<T: Config> ContractInfo<T> {
 	}
 }
 
+/// The maximum storage a contract may occupy.
+///
+/// Writes that would grow the storage of a contract beyond its limit fail with
+/// [`Error::StorageExhausted`]. Writes that do not grow it always succeed, so a contract above
+/// a lowered limit can still free storage.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct StorageLimit {
+	/// The maximum sum of the sizes of all stored values in bytes.
+	pub max_bytes: u32,
+	/// The maximum number of key-value pairs.
+	pub max_items: u32,
+}
+
+/// The storage occupied by a contract and the limit it is subject to.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct StorageUsage {
+	/// The sum of the sizes of all stored values in bytes.
+	pub bytes: u32,
+	/// The number of key-value pairs.
+	pub items: u32,
+	/// The limit of the contract or `None` if it is not limited.
+	pub limit: Option<StorageLimit>,
+}
+
 /// Information for managing an account and its sub trie abstraction.
 /// This is the required info to cache for an account.
 #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
@@ -170,6 +200,53 @@ where
 		child::get_raw(&child_trie_info(&trie_id), &blake2_256(key))
 	}
 
+	/// Lists up to `limit` keys of a contract's child trie which start with `prefix`.
+	///
+	/// These are the keys as stored in the trie which are the hashes of the keys the contract
+	/// uses. The keys are returned in order, starting after `start_key` if supplied.
+	///
+	/// This iterates the trie and must therefore only be used off-chain.
+	pub fn keys(
+		trie_id: &TrieId,
+		prefix: &[u8],
+		start_key: Option<&[u8]>,
+		limit: u32,
+	) -> Vec<Vec<u8>> {
+		let child_info = child_trie_info(&trie_id);
+		let next_key = |key: &[u8]| {
+			sp_io::default_child_storage::next_key(child_info.storage_key(), key)
+		};
+		let mut next = match start_key {
+			Some(start_key) if start_key >= prefix => next_key(start_key),
+			_ if child::exists(&child_info, prefix) => Some(prefix.to_vec()),
+			_ => next_key(prefix),
+		};
+		let mut keys = Vec::new();
+		while let Some(key) = next {
+			if keys.len() as u32 >= limit || !key.starts_with(prefix) {
+				break;
+			}
+			next = next_key(&key);
+			keys.push(key);
+		}
+		keys
+	}
+
+	/// Lists up to `limit` key-value pairs of a contract's child trie.
+	///
+	/// The pairs are listed like the keys of [`Self::keys`] without a prefix.
+	pub fn entries(
+		trie_id: &TrieId,
+		start_key: Option<&[u8]>,
+		limit: u32,
+	) -> Vec<(Vec<u8>, Vec<u8>)> {
+		let child_info = child_trie_info(&trie_id);
+		Self::keys(trie_id, &[], start_key, limit)
+			.into_iter()
+			.filter_map(|key| child::get_raw(&child_info, &key).map(|value| (key, value)))
+			.collect()
+	}
+
 	/// Update a storage entry into a contract's kv storage.
 	///
 	/// If the `opt_new_value` is `None` then the kv pair is removed.
@@ -180,6 +257,9 @@ where
 	///
 	/// If the contract specified by the id `account` doesn't exist `Err` is returned.`
 	///
+	/// Writes that grow the storage beyond `limit` fail. The caller passes the limit in so that
+	/// it is read once per execution of a contract rather than on every write.
+	///
 	/// # Panics
 	///
 	/// Panics iff the `account` specified is not alive and in storage.
@@ -188,6 +268,7 @@ where
 		trie_id: &TrieId,
 		key: &StorageKey,
 		opt_new_value: Option<Vec<u8>>,
+		limit: Option<StorageLimit>,
 	) -> DispatchResult {
 		let mut new_info = match <ContractInfoOf<T>>::get(account) {
 			Some(ContractInfo::Alive(alive)) => alive,
@@ -198,6 +279,7 @@ where
 		let child_trie_info = &child_trie_info(&trie_id);
 
 		let opt_prev_len = child::len(&child_trie_info, &hashed_key);
+		let (prev_pair_count, prev_storage_size) = (new_info.pair_count, new_info.storage_size);
 
 		// Update the total number of KV pairs and the number of empty pairs.
 		match (&opt_prev_len, &opt_new_value) {
@@ -225,6 +307,18 @@ where
 			.and_then(|val| val.checked_add(new_value_len))
 			.ok_or_else(|| Error::<T>::StorageExhausted)?;
 
+		// Only writes that grow the storage are checked against the limit.
+		if new_info.pair_count > prev_pair_count || new_info.storage_size > prev_storage_size {
+			if let Some(limit) = limit {
+				if (new_info.pair_count > prev_pair_count && new_info.pair_count > limit.max_items) ||
+					(new_info.storage_size > prev_storage_size &&
+						new_info.storage_size > limit.max_bytes)
+				{
+					return Err(Error::<T>::StorageExhausted.into());
+				}
+			}
+		}
+
 		new_info.last_write = Some(<frame_system::Pallet<T>>::block_number());
 		<ContractInfoOf<T>>::insert(&account, ContractInfo::Alive(new_info));
 
@@ -17,7 +17,8 @@
 
 use crate::{
 	BalanceOf, ContractInfo, ContractInfoOf, Pallet,
-	Config, Schedule,
+	Config, Limits, Schedule, SchedulePreset, StorageLimit, StorageUsage,
+	CallKind, record_frames,
 	Error, storage::Storage,
 	chain_extension::{
 		Result as ExtensionResult, Environment, ChainExtension, Ext, SysConfig, RetVal,
@@ -29,7 +30,8 @@ use crate::{
 	storage::RawAliveContractInfo,
 };
 use assert_matches::assert_matches;
-use codec::Encode;
+use codec::{Decode, Encode};
+use pallet_contracts_primitives::{Code, ContractAccessError, RentProjection};
 use sp_core::Bytes;
 use sp_runtime::{
 	traits::{BlakeTwo256, Hash, IdentityLookup, Convert},
@@ -42,7 +44,7 @@ use frame_support::{
 	parameter_types, assert_storage_noop,
 	traits::{Currency, ReservableCurrency, OnInitialize, GenesisBuild},
 	weights::{Weight, PostDispatchInfo, DispatchClass, constants::WEIGHT_PER_SECOND},
-	dispatch::DispatchErrorWithPostInfo,
+	dispatch::{DispatchErrorWithPostInfo, DispatchError},
 	storage::child,
 };
 use frame_system::{self as system, EventRecord, Phase};
@@ -64,6 +66,7 @@ frame_support::construct_runtime!(
 		Timestamp: pallet_timestamp::{Pallet, Call, Storage, Inherent},
 		Randomness: pallet_randomness_collective_flip::{Pallet, Call, Storage},
 		Contracts: pallet_contracts::{Pallet, Call, Config<T>, Storage, Event<T>},
+		Sudo: pallet_sudo::{Pallet, Call, Config<T>, Storage, Event<T>},
 	}
 );
 
@@ -80,7 +83,7 @@ pub mod test_utils {
 
 	pub fn set_storage(addr: &AccountIdOf<Test>, key: &StorageKey, value: Option<Vec<u8>>) {
 		let contract_info = <ContractInfoOf::<Test>>::get(&addr).unwrap().get_alive().unwrap();
-		Storage::<Test>::write(addr, &contract_info.trie_id, key, value).unwrap();
+		Storage::<Test>::write(addr, &contract_info.trie_id, key, value, None).unwrap();
 	}
 	pub fn get_storage(addr: &AccountIdOf<Test>, key: &StorageKey) -> Option<Vec<u8>> {
 		let contract_info = <ContractInfoOf::<Test>>::get(&addr).unwrap().get_alive().unwrap();
@@ -243,6 +246,10 @@ impl pallet_timestamp::Config for Test {
 	type MinimumPeriod = MinimumPeriod;
 	type WeightInfo = ();
 }
+impl pallet_sudo::Config for Test {
+	type Event = Event;
+	type Call = Call;
+}
 parameter_types! {
 	pub const SignedClaimHandicap: u64 = 2;
 	pub const TombstoneDeposit: u64 = 16;
@@ -251,11 +258,14 @@ parameter_types! {
 	pub const DepositPerStorageItem: u64 = 10_000;
 	pub RentFraction: Perbill = Perbill::from_rational(4u32, 10_000u32);
 	pub const SurchargeReward: u64 = 500_000;
+	pub static RentFree: bool = false;
 	pub const MaxDepth: u32 = 100;
 	pub const MaxValueSize: u32 = 16_384;
 	pub const DeletionQueueDepth: u32 = 1024;
 	pub const DeletionWeightLimit: Weight = 500_000_000_000;
 	pub const MaxCodeSize: u32 = 2 * 1024;
+	pub const MaxMetadataLen: u32 = 1024;
+	pub const MaxDebugBufferLen: u32 = 2 * 1024;
 }
 
 parameter_types! {
@@ -281,6 +291,7 @@ impl Config for Test {
 	type DepositPerStorageItem = DepositPerStorageItem;
 	type RentFraction = RentFraction;
 	type SurchargeReward = SurchargeReward;
+	type RentFree = RentFree;
 	type MaxDepth = MaxDepth;
 	type MaxValueSize = MaxValueSize;
 	type WeightPrice = Self;
@@ -289,6 +300,8 @@ impl Config for Test {
 	type DeletionQueueDepth = DeletionQueueDepth;
 	type DeletionWeightLimit = DeletionWeightLimit;
 	type MaxCodeSize = MaxCodeSize;
+	type MaxMetadataLen = MaxMetadataLen;
+	type MaxDebugBufferLen = MaxDebugBufferLen;
 }
 
 pub const ALICE: AccountId32 = AccountId32::new([1u8; 32]);
@@ -300,11 +313,13 @@ const GAS_LIMIT: Weight = 10_000_000_000;
 
 pub struct ExtBuilder {
 	existential_deposit: u64,
+	rent_free: bool,
 }
 impl Default for ExtBuilder {
 	fn default() -> Self {
 		Self {
 			existential_deposit: 1,
+			rent_free: false,
 		}
 	}
 }
@@ -313,8 +328,13 @@ impl ExtBuilder {
 		self.existential_deposit = existential_deposit;
 		self
 	}
+	pub fn rent_free(mut self, rent_free: bool) -> Self {
+		self.rent_free = rent_free;
+		self
+	}
 	pub fn set_associated_consts(&self) {
 		EXISTENTIAL_DEPOSIT.with(|v| *v.borrow_mut() = self.existential_deposit);
+		RENT_FREE.with(|v| *v.borrow_mut() = self.rent_free);
 	}
 	pub fn build(self) -> sp_io::TestExternalities {
 		self.set_associated_consts();
@@ -328,6 +348,9 @@ impl ExtBuilder {
 				..Default::default()
 			},
 		}.assimilate_storage(&mut t).unwrap();
+		pallet_sudo::GenesisConfig::<Test> {
+			key: ALICE,
+		}.assimilate_storage(&mut t).unwrap();
 		let mut ext = sp_io::TestExternalities::new(t);
 		ext.execute_with(|| System::set_block_number(1));
 		ext
@@ -696,6 +719,108 @@ fn storage_size() {
 		});
 }
 
+#[test]
+fn storage_limits_are_enforced() {
+	let (wasm, code_hash) = compile_module::<Test>("set_rent").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let default_limit = StorageLimit { max_bytes: 4, max_items: 1 };
+		assert_noop!(
+			Contracts::set_default_storage_limit(Origin::signed(ALICE), Some(default_limit)),
+			DispatchError::BadOrigin,
+		);
+		assert_ok!(Contracts::set_default_storage_limit(Origin::root(), Some(default_limit)));
+
+		// The constructor stores 4 bytes in 1 item which is just within the limit.
+		assert_ok!(Contracts::instantiate_with_code(
+			Origin::signed(ALICE),
+			30_000,
+			GAS_LIMIT,
+			wasm,
+			<BalanceOf<Test>>::from(10_000u32).encode(), // rent allowance
+			vec![],
+		));
+		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+		assert_eq!(
+			Contracts::storage_usage(&addr),
+			Some(StorageUsage { bytes: 4, items: 1, limit: Some(default_limit) }),
+		);
+		assert_err_ignore_postinfo!(
+			Contracts::call(
+				Origin::signed(ALICE),
+				addr.clone(),
+				0,
+				GAS_LIMIT,
+				call::set_storage_4_byte(),
+			),
+			Error::<Test>::StorageExhausted,
+		);
+
+		// The limit of the contract overrides the default limit.
+		let limit = StorageLimit { max_bytes: 8, max_items: 2 };
+		assert_ok!(Contracts::set_storage_limit(Origin::root(), addr.clone(), Some(limit)));
+		assert_ok!(Contracts::call(
+			Origin::signed(ALICE),
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			call::set_storage_4_byte(),
+		));
+		assert_eq!(
+			Contracts::storage_usage(&addr),
+			Some(StorageUsage { bytes: 8, items: 2, limit: Some(limit) }),
+		);
+
+		// Storage can always be freed, even above a lowered limit.
+		assert_ok!(Contracts::set_storage_limit(Origin::root(), addr.clone(), None));
+		assert_ok!(Contracts::call(
+			Origin::signed(ALICE),
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			call::remove_storage_4_byte(),
+		));
+		assert_eq!(
+			Contracts::storage_usage(&addr),
+			Some(StorageUsage { bytes: 4, items: 1, limit: Some(default_limit) }),
+		);
+	});
+}
+
+#[test]
+fn on_chain_executions_are_recorded_on_request() {
+	let (wasm, code_hash) = compile_module::<Test>("set_rent").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let (result, frames) = record_frames::<Test, _>(|| Contracts::instantiate_with_code(
+			Origin::signed(ALICE),
+			30_000,
+			GAS_LIMIT,
+			wasm,
+			<BalanceOf<Test>>::from(10_000u32).encode(), // rent allowance
+			vec![],
+		));
+		assert_ok!(result);
+		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+		assert_eq!(frames.len(), 1);
+		assert_eq!((frames[0].kind, &frames[0].callee), (CallKind::Instantiate, &addr));
+		assert_eq!(frames[0].code_hash, Some(code_hash));
+
+		let (result, frames) = record_frames::<Test, _>(|| Contracts::call(
+			Origin::signed(ALICE),
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			call::set_storage_4_byte(),
+		));
+		assert_ok!(result);
+		assert_eq!(frames.len(), 1);
+		let write = frames[0].storage.iter().find(|access| access.write).unwrap();
+		assert_eq!(write.previous, None);
+		assert_eq!(write.value.as_ref().map(|value| value.len()), Some(4));
+	});
+}
+
 #[test]
 fn empty_kv_pairs() {
 	let (wasm, code_hash) = compile_module::<Test>("set_empty_storage").unwrap();
@@ -1887,6 +2012,7 @@ fn crypto_hashes() {
 					0,
 					GAS_LIMIT,
 					params,
+					false,
 				).result.unwrap();
 				assert!(result.is_success());
 				let expected = hash_fn(input.as_ref());
@@ -1922,6 +2048,7 @@ fn transfer_return_code() {
 			0,
 			GAS_LIMIT,
 			vec![],
+			false,
 		).result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::BelowSubsistenceThreshold);
 
@@ -1936,6 +2063,7 @@ fn transfer_return_code() {
 			0,
 			GAS_LIMIT,
 			vec![],
+			false,
 		).result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::TransferFailed);
 	});
@@ -1970,6 +2098,7 @@ fn call_return_code() {
 			0,
 			GAS_LIMIT,
 			AsRef::<[u8]>::as_ref(&DJANGO).to_vec(),
+			false,
 		).result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::NotCallable);
 
@@ -1993,6 +2122,7 @@ fn call_return_code() {
 			0,
 			GAS_LIMIT,
 			AsRef::<[u8]>::as_ref(&addr_django).iter().chain(&0u32.to_le_bytes()).cloned().collect(),
+			false,
 		).result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::BelowSubsistenceThreshold);
 
@@ -2007,6 +2137,7 @@ fn call_return_code() {
 			0,
 			GAS_LIMIT,
 			AsRef::<[u8]>::as_ref(&addr_django).iter().chain(&0u32.to_le_bytes()).cloned().collect(),
+			false,
 		).result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::TransferFailed);
 
@@ -2018,6 +2149,7 @@ fn call_return_code() {
 			0,
 			GAS_LIMIT,
 			AsRef::<[u8]>::as_ref(&addr_django).iter().chain(&1u32.to_le_bytes()).cloned().collect(),
+			false,
 		).result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::CalleeReverted);
 
@@ -2028,6 +2160,7 @@ fn call_return_code() {
 			0,
 			GAS_LIMIT,
 			AsRef::<[u8]>::as_ref(&addr_django).iter().chain(&2u32.to_le_bytes()).cloned().collect(),
+			false,
 		).result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::CalleeTrapped);
 
@@ -2075,6 +2208,7 @@ fn instantiate_return_code() {
 			0,
 			GAS_LIMIT,
 			callee_hash.clone(),
+			false,
 		).result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::BelowSubsistenceThreshold);
 
@@ -2089,6 +2223,7 @@ fn instantiate_return_code() {
 			0,
 			GAS_LIMIT,
 			callee_hash.clone(),
+			false,
 		).result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::TransferFailed);
 
@@ -2100,6 +2235,7 @@ fn instantiate_return_code() {
 			0,
 			GAS_LIMIT,
 			vec![0; 33],
+			false,
 		).result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::CodeNotFound);
 
@@ -2110,6 +2246,7 @@ fn instantiate_return_code() {
 			0,
 			GAS_LIMIT,
 			callee_hash.iter().chain(&1u32.to_le_bytes()).cloned().collect(),
+			false,
 		).result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::CalleeReverted);
 
@@ -2120,6 +2257,7 @@ fn instantiate_return_code() {
 			0,
 			GAS_LIMIT,
 			callee_hash.iter().chain(&2u32.to_le_bytes()).cloned().collect(),
+			false,
 		).result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::CalleeTrapped);
 
@@ -2207,6 +2345,7 @@ fn chain_extension_works() {
 			0,
 			GAS_LIMIT,
 			vec![0, 99],
+			false,
 		);
 		let gas_consumed = result.gas_consumed;
 		assert_eq!(TestExtension::last_seen_buffer(), vec![0, 99]);
@@ -2219,6 +2358,7 @@ fn chain_extension_works() {
 			0,
 			GAS_LIMIT,
 			vec![1],
+			false,
 		).result.unwrap();
 		// those values passed in the fixture
 		assert_eq!(TestExtension::last_seen_inputs(), (4, 1, 16, 12));
@@ -2230,6 +2370,7 @@ fn chain_extension_works() {
 			0,
 			GAS_LIMIT,
 			vec![2, 42],
+			false,
 		);
 		assert_ok!(result.result);
 		assert_eq!(result.gas_consumed, gas_consumed + 42);
@@ -2241,6 +2382,7 @@ fn chain_extension_works() {
 			0,
 			GAS_LIMIT,
 			vec![3],
+			false,
 		).result.unwrap();
 		assert_eq!(result.flags, ReturnFlags::REVERT);
 		assert_eq!(result.data, Bytes(vec![42, 99]));
@@ -2336,6 +2478,7 @@ fn lazy_removal_partial_remove_works() {
 				&info.trie_id,
 				&val.0,
 				Some(val.2.clone()),
+				None,
 			).unwrap();
 		}
 
@@ -2424,6 +2567,7 @@ fn lazy_removal_does_no_run_on_full_block() {
 				&info.trie_id,
 				&val.0,
 				Some(val.2.clone()),
+				None,
 			).unwrap();
 		}
 
@@ -2509,6 +2653,7 @@ fn lazy_removal_does_not_use_all_weight() {
 				&info.trie_id,
 				&val.0,
 				Some(val.2.clone()),
+				None,
 			).unwrap();
 		}
 
@@ -2667,6 +2812,67 @@ fn surcharge_reward_is_capped() {
 	});
 }
 
+#[test]
+fn rent_free_contracts_are_never_evicted() {
+	let (wasm, code_hash) = compile_module::<Test>("set_rent").unwrap();
+	ExtBuilder::default().existential_deposit(50).rent_free(true).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+
+		// Far too low to pay the rent for the first block if rent was enabled.
+		assert_ok!(Contracts::instantiate_with_code(
+			Origin::signed(ALICE),
+			30_000,
+			GAS_LIMIT,
+			wasm,
+			<BalanceOf<Test>>::from(1u32).encode(), // rent allowance
+			vec![],
+		));
+		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+		assert_eq!(Contracts::rent_projection(addr.clone()), Ok(RentProjection::NoEviction));
+
+		initialize_block(40);
+		assert_err_ignore_postinfo!(
+			Contracts::claim_surcharge(Origin::none(), addr.clone(), Some(ALICE)),
+			Error::<Test>::RentDisabled,
+		);
+		assert_ok!(
+			Contracts::call(Origin::signed(ALICE), addr.clone(), 0, GAS_LIMIT, call::null())
+		);
+
+		let contract = ContractInfoOf::<Test>::get(&addr).unwrap().get_alive().unwrap();
+		assert_eq!(contract.rent_payed, 0);
+		assert_eq!(Balances::free_balance(&addr), 30_000);
+	});
+}
+
+#[test]
+fn tombstones_are_removed_when_rent_is_disabled() {
+	let (wasm, code_hash) = compile_module::<Test>("set_rent").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_ok!(Contracts::instantiate_with_code(
+			Origin::signed(ALICE),
+			30_000,
+			GAS_LIMIT,
+			wasm,
+			<BalanceOf<Test>>::from(10_000u32).encode(), // rent allowance
+			vec![],
+		));
+		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+		initialize_block(40);
+		assert_ok!(Contracts::claim_surcharge(Origin::none(), addr.clone(), Some(ALICE)));
+		assert!(ContractInfoOf::<Test>::get(&addr).unwrap().get_tombstone().is_some());
+
+		// Tombstones are kept as long as rent is enabled.
+		crate::migration::migrate::<Test>();
+		assert!(ContractInfoOf::<Test>::get(&addr).is_some());
+
+		RENT_FREE.with(|v| *v.borrow_mut() = true);
+		crate::migration::migrate::<Test>();
+		assert_matches!(ContractInfoOf::<Test>::get(&addr), None);
+	});
+}
+
 #[test]
 fn refcounter() {
 	let (wasm, code_hash) = compile_module::<Test>("self_destruct").unwrap();
@@ -2739,6 +2945,30 @@ fn refcounter() {
 	});
 }
 
+#[test]
+fn metadata_is_removed_with_code() {
+	let (wasm, code_hash) = compile_module::<Test>("return_from_start_fn").unwrap();
+	ExtBuilder::default().build().execute_with(|| {
+		assert_err!(
+			Contracts::attach_metadata(code_hash, vec![1]),
+			Error::<Test>::CodeNotFound,
+		);
+		assert_ok!(Contracts::add_deployer(Origin::root(), BOB, None));
+		assert_ok!(Contracts::upload_code(Origin::signed(BOB), wasm));
+		assert_err!(
+			Contracts::attach_metadata(code_hash, vec![0; 1025]),
+			Error::<Test>::MetadataTooLarge,
+		);
+		assert_eq!(Contracts::attach_metadata(code_hash, vec![1]), Ok(true));
+		// Metadata cannot be replaced.
+		assert_eq!(Contracts::attach_metadata(code_hash, vec![2]), Ok(false));
+		assert_eq!(Contracts::code_metadata(&code_hash), Some(vec![1]));
+
+		assert_ok!(Contracts::reject_code(Origin::root(), code_hash));
+		assert_eq!(Contracts::code_metadata(&code_hash), None);
+	});
+}
+
 #[test]
 fn reinstrument_does_charge() {
 	let (wasm, code_hash) = compile_module::<Test>("return_with_data").unwrap();
@@ -2767,6 +2997,7 @@ fn reinstrument_does_charge() {
 			0,
 			GAS_LIMIT,
 			zero.clone(),
+			false,
 		);
 		assert!(result0.result.unwrap().is_success());
 
@@ -2776,6 +3007,7 @@ fn reinstrument_does_charge() {
 			0,
 			GAS_LIMIT,
 			zero.clone(),
+			false,
 		);
 		assert!(result1.result.unwrap().is_success());
 
@@ -2794,6 +3026,7 @@ fn reinstrument_does_charge() {
 			0,
 			GAS_LIMIT,
 			zero.clone(),
+			false,
 		);
 		assert!(result2.result.unwrap().is_success());
 		assert!(result2.gas_consumed > result1.gas_consumed);
@@ -2803,3 +3036,441 @@ fn reinstrument_does_charge() {
 		);
 	});
 }
+
+#[test]
+fn only_root_can_manage_deployers() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_err_ignore_postinfo!(
+			Contracts::add_deployer(Origin::signed(ALICE), BOB, None),
+			DispatchError::BadOrigin,
+		);
+		assert_ok!(Contracts::add_deployer(Origin::root(), BOB, Some(10)));
+		assert_eq!(crate::Deployers::<Test>::get(&BOB), Some(Some(10)));
+		assert_err_ignore_postinfo!(
+			Contracts::remove_deployer(Origin::signed(BOB), BOB),
+			DispatchError::BadOrigin,
+		);
+		assert_ok!(Contracts::remove_deployer(Origin::root(), BOB));
+		assert!(!crate::Deployers::<Test>::contains_key(&BOB));
+		assert_err_ignore_postinfo!(
+			Contracts::remove_deployer(Origin::root(), BOB),
+			Error::<Test>::NotAuthorizedDeployer,
+		);
+	});
+}
+
+#[test]
+fn deployer_allowlist_gates_instantiation() {
+	let (wasm, code_hash) = compile_module::<Test>("return_from_start_fn").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let _ = Balances::deposit_creating(&BOB, 1_000_000);
+		let subsistence = Pallet::<Test>::subsistence_threshold();
+
+		// BOB is neither the sudo key nor on the allowlist.
+		assert_err_ignore_postinfo!(
+			Contracts::instantiate_with_code(
+				Origin::signed(BOB),
+				subsistence * 100,
+				GAS_LIMIT,
+				wasm.clone(),
+				vec![],
+				vec![],
+			),
+			Error::<Test>::NotAuthorizedDeployer,
+		);
+
+		// The sudo key can always deploy.
+		assert_ok!(Contracts::instantiate_with_code(
+			Origin::signed(ALICE),
+			subsistence * 100,
+			GAS_LIMIT,
+			wasm,
+			vec![],
+			vec![],
+		));
+
+		// Once allowed BOB can deploy from the stored code.
+		assert_ok!(Contracts::add_deployer(Origin::root(), BOB, None));
+		assert_ok!(Contracts::instantiate(
+			Origin::signed(BOB),
+			subsistence * 100,
+			GAS_LIMIT,
+			code_hash,
+			vec![],
+			vec![],
+		));
+
+		// After revocation BOB is rejected again.
+		assert_ok!(Contracts::remove_deployer(Origin::root(), BOB));
+		assert_err_ignore_postinfo!(
+			Contracts::instantiate(
+				Origin::signed(BOB),
+				subsistence * 100,
+				GAS_LIMIT,
+				code_hash,
+				vec![],
+				vec![1],
+			),
+			Error::<Test>::NotAuthorizedDeployer,
+		);
+	});
+}
+
+#[test]
+fn bare_instantiate_requires_deployer() {
+	let (wasm, code_hash) = compile_module::<Test>("return_from_start_fn").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let _ = Balances::deposit_creating(&BOB, 1_000_000);
+		let subsistence = Pallet::<Test>::subsistence_threshold();
+
+		let result = Contracts::bare_instantiate(
+			BOB,
+			subsistence * 100,
+			GAS_LIMIT,
+			Code::Upload(Bytes(wasm.clone())),
+			vec![],
+			vec![],
+			true,
+			false,
+		);
+		assert_eq!(result.result, Err(Error::<Test>::NotAuthorizedDeployer.into()));
+		assert_eq!(result.gas_consumed, 0);
+
+		// The sudo key can always deploy.
+		let result = Contracts::bare_instantiate(
+			ALICE,
+			subsistence * 100,
+			GAS_LIMIT,
+			Code::Upload(Bytes(wasm)),
+			vec![],
+			vec![],
+			true,
+			false,
+		);
+		let result = result.result.unwrap();
+		assert_eq!(result.account_id, Contracts::contract_address(&ALICE, &code_hash, &[]));
+		assert!(result.rent_projection.is_some());
+	});
+}
+
+#[test]
+fn deployer_permission_expires() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(Contracts::add_deployer(Origin::root(), BOB, Some(3)));
+		assert!(Contracts::is_deployer(&BOB));
+
+		let schedule = <Schedule<Test>>::default();
+		assert_ok!(Contracts::update_schedule(Origin::signed(BOB), schedule.clone()));
+
+		System::set_block_number(3);
+		assert!(!Contracts::is_deployer(&BOB));
+		assert_err_ignore_postinfo!(
+			Contracts::update_schedule(Origin::signed(BOB), schedule),
+			Error::<Test>::NotAuthorizedDeployer,
+		);
+	});
+}
+
+#[test]
+fn uploaded_code_needs_approval() {
+	let (wasm, code_hash) = compile_module::<Test>("return_from_start_fn").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let subsistence = Pallet::<Test>::subsistence_threshold();
+
+		// Only deployers may upload code.
+		assert_err_ignore_postinfo!(
+			Contracts::upload_code(Origin::signed(BOB), wasm.clone()),
+			Error::<Test>::NotAuthorizedDeployer,
+		);
+		assert_ok!(Contracts::add_deployer(Origin::root(), BOB, None));
+		assert_ok!(Contracts::upload_code(Origin::signed(BOB), wasm.clone()));
+		assert_refcount!(code_hash, 0);
+		assert_eq!(crate::PendingCode::<Test>::get(code_hash), Some(BOB));
+		assert_err_ignore_postinfo!(
+			Contracts::upload_code(Origin::signed(BOB), wasm),
+			Error::<Test>::CodeAlreadyStored,
+		);
+
+		assert_err_ignore_postinfo!(
+			Contracts::instantiate(
+				Origin::signed(ALICE),
+				subsistence * 100,
+				GAS_LIMIT,
+				code_hash,
+				vec![],
+				vec![],
+			),
+			Error::<Test>::CodeNotApproved,
+		);
+
+		assert_err_ignore_postinfo!(
+			Contracts::approve_code(Origin::signed(ALICE), code_hash),
+			DispatchError::BadOrigin,
+		);
+		assert_ok!(Contracts::approve_code(Origin::root(), code_hash));
+		assert!(!crate::PendingCode::<Test>::contains_key(code_hash));
+		assert_err_ignore_postinfo!(
+			Contracts::reject_code(Origin::root(), code_hash),
+			Error::<Test>::CodeNotPending,
+		);
+
+		assert_ok!(Contracts::instantiate(
+			Origin::signed(ALICE),
+			subsistence * 100,
+			GAS_LIMIT,
+			code_hash,
+			vec![],
+			vec![],
+		));
+		assert_refcount!(code_hash, 1);
+	});
+}
+
+#[test]
+fn rejected_code_is_removed() {
+	let (wasm, code_hash) = compile_module::<Test>("return_from_start_fn").unwrap();
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(Contracts::add_deployer(Origin::root(), BOB, None));
+		assert_ok!(Contracts::upload_code(Origin::signed(BOB), wasm));
+		assert!(crate::PristineCode::<Test>::contains_key(code_hash));
+
+		assert_ok!(Contracts::reject_code(Origin::root(), code_hash));
+		assert_matches!(crate::PristineCode::<Test>::get(code_hash), None);
+		assert_matches!(crate::CodeStorage::<Test>::get(code_hash), None);
+		assert!(!crate::PendingCode::<Test>::contains_key(code_hash));
+		assert!(System::events().iter().any(|record| record.event ==
+			Event::pallet_contracts(crate::Event::CodeRejected(code_hash))
+		));
+	});
+}
+
+#[test]
+fn set_contract_code_keeps_storage() {
+	use self::test_utils::{set_storage, get_storage};
+
+	let (wasm, code_hash) = compile_module::<Test>("return_from_start_fn").unwrap();
+	let (new_wasm, new_code_hash) = compile_module::<Test>("dummy").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let subsistence = Pallet::<Test>::subsistence_threshold();
+		assert_ok!(Contracts::instantiate_with_code(
+			Origin::signed(ALICE),
+			subsistence * 100,
+			GAS_LIMIT,
+			wasm,
+			vec![],
+			vec![],
+		));
+		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+		set_storage(&addr, &[1u8; 32], Some(b"1".to_vec()));
+
+		assert_err!(
+			Contracts::set_contract_code(&addr, new_code_hash),
+			Error::<Test>::CodeNotFound,
+		);
+		assert_ok!(Contracts::add_deployer(Origin::root(), BOB, None));
+		assert_ok!(Contracts::upload_code(Origin::signed(BOB), new_wasm));
+		assert_err!(
+			Contracts::set_contract_code(&addr, new_code_hash),
+			Error::<Test>::CodeNotApproved,
+		);
+		assert_ok!(Contracts::approve_code(Origin::root(), new_code_hash));
+
+		assert_eq!(Contracts::set_contract_code(&addr, new_code_hash), Ok(code_hash));
+		assert_refcount!(code_hash, 0);
+		assert_refcount!(new_code_hash, 1);
+		assert_eq!(
+			ContractInfoOf::<Test>::get(&addr).unwrap().get_alive().unwrap().code_hash,
+			new_code_hash,
+		);
+		assert_eq!(get_storage(&addr, &[1u8; 32]), Some(b"1".to_vec()));
+
+		assert_err!(
+			Contracts::set_contract_code(&BOB, new_code_hash),
+			Error::<Test>::NotCallable,
+		);
+	});
+}
+
+#[test]
+fn paused_contracts_cannot_be_executed() {
+	let (wasm, code_hash) = compile_module::<Test>("return_from_start_fn").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let subsistence = Pallet::<Test>::subsistence_threshold();
+		assert_ok!(Contracts::instantiate_with_code(
+			Origin::signed(ALICE),
+			subsistence * 100,
+			GAS_LIMIT,
+			wasm,
+			vec![],
+			vec![],
+		));
+		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+
+		assert_err!(
+			Contracts::pause_contract(Origin::signed(ALICE), addr.clone()),
+			DispatchError::BadOrigin,
+		);
+		assert_ok!(Contracts::pause_contract(Origin::root(), addr.clone()));
+		assert!(Contracts::is_paused(&addr));
+		assert_err_ignore_postinfo!(
+			Contracts::call(Origin::signed(ALICE), addr.clone(), 0, GAS_LIMIT, vec![]),
+			Error::<Test>::ContractPaused,
+		);
+		assert_ok!(Contracts::unpause_contract(Origin::root(), addr.clone()));
+		assert_err!(
+			Contracts::unpause_contract(Origin::root(), addr.clone()),
+			Error::<Test>::ContractNotPaused,
+		);
+		assert_ok!(Contracts::call(Origin::signed(ALICE), addr.clone(), 0, GAS_LIMIT, vec![]));
+
+		assert_ok!(Contracts::set_halted(Origin::root(), true));
+		assert!(Contracts::is_paused(&addr));
+		assert_err_ignore_postinfo!(
+			Contracts::call(Origin::signed(ALICE), addr.clone(), 0, GAS_LIMIT, vec![]),
+			Error::<Test>::ContractPaused,
+		);
+		assert_err_ignore_postinfo!(
+			Contracts::instantiate(
+				Origin::signed(ALICE),
+				subsistence * 100,
+				GAS_LIMIT,
+				code_hash,
+				vec![],
+				vec![1],
+			),
+			Error::<Test>::ContractPaused,
+		);
+		assert_ok!(Contracts::set_halted(Origin::root(), false));
+		assert!(!Contracts::is_paused(&addr));
+	});
+}
+
+#[test]
+fn update_schedule_cannot_lower_limits() {
+	ExtBuilder::default().build().execute_with(|| {
+		let large = <Schedule<Test>>::from(SchedulePreset::LargeContracts);
+		assert_ok!(Contracts::update_schedule(Origin::root(), large.clone()));
+		assert_eq!(crate::CurrentSchedule::<Test>::get(), large);
+
+		assert_err_ignore_postinfo!(
+			Contracts::update_schedule(Origin::root(), <Schedule<Test>>::default()),
+			Error::<Test>::ScheduleLimitsLowered,
+		);
+		let more_pages = Limits { memory_pages: 128, ..large.limits().clone() };
+		assert_ok!(Contracts::update_schedule(
+			Origin::root(),
+			large.with_version(1).with_limits(more_pages),
+		));
+	});
+}
+
+#[test]
+fn storage_keys_are_paged_and_read_at_once() {
+	use self::test_utils::{place_contract, set_storage};
+
+	ExtBuilder::default().build().execute_with(|| {
+		place_contract(&BOB, H256::repeat_byte(1));
+		for i in 0..4u8 {
+			set_storage(&BOB, &[i; 32], Some(vec![i]));
+		}
+		let mut keys: Vec<Vec<u8>> = (0..4u8).map(|i| blake2_256(&[i; 32]).to_vec()).collect();
+		keys.sort();
+
+		assert_eq!(Contracts::storage_keys(BOB, vec![], None, 10), Ok(keys.clone()));
+
+		// Pages start after the last key of the previous page.
+		let page = Contracts::storage_keys(BOB, vec![], None, 3).unwrap();
+		assert_eq!(page, keys[..3].to_vec());
+		let page = Contracts::storage_keys(BOB, vec![], Some(page[2].clone()), 3).unwrap();
+		assert_eq!(page, keys[3..].to_vec());
+
+		// Only keys with the prefix are listed. A whole key is a prefix of itself.
+		assert_eq!(Contracts::storage_keys(BOB, keys[1].clone(), None, 10), Ok(vec![keys[1].clone()]));
+		let prefix = keys[1][..1].to_vec();
+		let with_prefix: Vec<_> = keys.iter().filter(|key| key.starts_with(&prefix)).cloned().collect();
+		assert_eq!(Contracts::storage_keys(BOB, prefix, None, 10), Ok(with_prefix));
+
+		assert_eq!(
+			Contracts::get_storage_many(BOB, vec![[2; 32], [9; 32], [0; 32]]),
+			Ok(vec![Some(vec![2]), None, Some(vec![0])]),
+		);
+		assert_eq!(
+			Contracts::get_storage_many(CHARLIE, vec![[2; 32]]),
+			Err(ContractAccessError::DoesntExist),
+		);
+		assert_eq!(
+			Contracts::storage_keys(CHARLIE, vec![], None, 10),
+			Err(ContractAccessError::DoesntExist),
+		);
+	});
+}
+
+#[test]
+fn storage_entries_are_listed_with_hashed_keys() {
+	use self::test_utils::{place_contract, set_storage};
+
+	ExtBuilder::default().build().execute_with(|| {
+		place_contract(&BOB, H256::repeat_byte(1));
+		for i in 0..3u8 {
+			set_storage(&BOB, &[i; 32], Some(vec![i; 2]));
+		}
+		let mut entries: Vec<_> = (0..3u8)
+			.map(|i| (blake2_256(&[i; 32]).to_vec(), vec![i; 2]))
+			.collect();
+		entries.sort();
+
+		assert_eq!(Contracts::storage_entries(BOB, None, 10), Ok(entries.clone()));
+		assert_eq!(Contracts::storage_entries(BOB, None, 1), Ok(entries[..1].to_vec()));
+		assert_eq!(
+			Contracts::storage_entries(BOB, Some(entries[0].0.clone()), 10),
+			Ok(entries[1..].to_vec()),
+		);
+		assert_eq!(
+			Contracts::storage_entries(CHARLIE, None, 10),
+			Err(ContractAccessError::DoesntExist),
+		);
+	});
+}
+
+#[test]
+fn storage_pages_are_capped() {
+	use self::test_utils::{place_contract, set_storage};
+
+	ExtBuilder::default().build().execute_with(|| {
+		place_contract(&BOB, H256::repeat_byte(1));
+		for i in 0..=crate::MAX_STORAGE_PAGE_LEN {
+			let mut key = [0; 32];
+			key[..4].copy_from_slice(&i.to_le_bytes());
+			set_storage(&BOB, &key, Some(vec![1]));
+		}
+
+		let max = crate::MAX_STORAGE_PAGE_LEN as usize;
+		assert_eq!(Contracts::storage_keys(BOB, vec![], None, u32::max_value()).unwrap().len(), max);
+		assert_eq!(Contracts::storage_entries(BOB, None, u32::max_value()).unwrap().len(), max);
+		let keys = vec![[0; 32]; max + 1];
+		assert_eq!(Contracts::get_storage_many(BOB, keys).unwrap().len(), max);
+	});
+}
+
+#[test]
+fn storage_proof_keys_locate_contract_info() {
+	ExtBuilder::default().build().execute_with(|| {
+		test_utils::place_contract(&BOB, H256::repeat_byte(1));
+		let (key, trie_id) = Contracts::storage_proof_keys(BOB).unwrap();
+
+		// Proof verifiers only decode the variant and the trie id of the raw info.
+		let raw_info = sp_io::storage::get(&key).unwrap();
+		assert_eq!(&raw_info[..1], &[0]);
+		assert_eq!(<Vec<u8>>::decode(&mut &raw_info[1..]).ok(), Some(trie_id));
+
+		assert_eq!(
+			Contracts::storage_proof_keys(CHARLIE),
+			Err(ContractAccessError::DoesntExist),
+		);
+	});
+}
@@ -0,0 +1,254 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2018-2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Recording of the call tree of a contract execution.
+//!
+//! Tracing is opt-in and only ever enabled for off-chain executions (RPC dry runs). The
+//! [`Tracer`] is attached to the top level execution context and shared with all nested
+//! contexts. Every call or instantiation pushes a [`CallFrame`] which collects the storage
+//! accesses and events of that frame until it returns.
+//!
+//! The frames are stored as a flat list in the order in which they were entered. The tree
+//! can be rebuilt from the `depth` of each frame: The parent of a frame is the closest
+//! preceding frame with a lower depth.
+//!
+//! On-chain executions are only traced within [`record_frames`] which allows to replay
+//! past extrinsics off-chain and inspect what their contract executions did.
+
+use crate::{
+	exec::{ErrorOrigin, ExecError, StorageKey},
+	BalanceOf, CodeHash, Config,
+};
+use codec::{Encode, Decode};
+#[cfg(feature = "std")]
+use serde::Serialize;
+use sp_core::Bytes;
+use sp_std::prelude::*;
+use sp_runtime::{DispatchError, RuntimeDebug};
+use frame_support::weights::Weight;
+use pallet_contracts_primitives::{ExecReturnValue, ReturnFlags};
+
+pub type CallFrameOf<T> =
+	CallFrame<<T as frame_system::Config>::AccountId, BalanceOf<T>, CodeHash<T>>;
+
+/// Whether a frame was created by a call or an instantiation.
+#[cfg_attr(feature = "std", derive(Serialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum CallKind {
+	/// A call to an existing contract.
+	Call,
+	/// The instantiation of a new contract. The callee is the new contract.
+	Instantiate,
+}
+
+/// A single read from or write to the storage of the executing contract.
+#[cfg_attr(feature = "std", derive(Serialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct StorageAccess {
+	/// The 32 byte key that was accessed.
+	pub key: Bytes,
+	/// The value that was read or written. `None` if the key was absent or was removed.
+	pub value: Option<Bytes>,
+	/// The value before it was written. Always `None` for reads.
+	pub previous: Option<Bytes>,
+	/// Whether the value was written (`true`) or read (`false`).
+	pub write: bool,
+}
+
+/// An event emitted by the executing contract through `seal_deposit_event`.
+#[cfg_attr(feature = "std", derive(Serialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct EmittedEvent<Hash> {
+	pub topics: Vec<Hash>,
+	pub data: Bytes,
+}
+
+/// Everything that was recorded about a single call or instantiation.
+#[cfg_attr(feature = "std", derive(Serialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct CallFrame<AccountId, Balance, Hash> {
+	/// The number of frames below this one. The top level frame has a depth of 0.
+	pub depth: u32,
+	pub kind: CallKind,
+	pub caller: AccountId,
+	pub callee: AccountId,
+	/// The code that was executed. `None` if the frame failed before the code was loaded.
+	pub code_hash: Option<Hash>,
+	pub value: Balance,
+	pub input: Bytes,
+	/// The data returned by the callee. `None` if the frame failed.
+	pub output: Option<Bytes>,
+	/// Whether the callee reverted its state changes on purpose.
+	pub reverted: bool,
+	/// The weight used by this frame including all of its nested frames.
+	pub gas_used: Weight,
+	/// The error the frame failed with.
+	pub error: Option<DispatchError>,
+	/// Whether the error happened while setting up the frame or while executing the callee.
+	pub error_origin: Option<ErrorOrigin>,
+	pub storage: Vec<StorageAccess>,
+	pub events: Vec<EmittedEvent<Hash>>,
+}
+
+/// Collects the frames of an execution.
+pub struct Tracer<T: Config> {
+	frames: Vec<CallFrameOf<T>>,
+	/// Indices into `frames` of all frames that have not yet returned.
+	stack: Vec<usize>,
+}
+
+impl<T: Config> Default for Tracer<T> {
+	fn default() -> Self {
+		Self {
+			frames: Vec::new(),
+			stack: Vec::new(),
+		}
+	}
+}
+
+impl<T: Config> Tracer<T> {
+	/// Returns all recorded frames in the order they were entered.
+	pub fn into_frames(self) -> Vec<CallFrameOf<T>> {
+		self.frames
+	}
+
+	/// Start a new frame nested into the currently executing one.
+	pub(crate) fn enter(
+		&mut self,
+		kind: CallKind,
+		caller: T::AccountId,
+		callee: T::AccountId,
+		value: BalanceOf<T>,
+		input: &[u8],
+	) {
+		self.stack.push(self.frames.len());
+		self.frames.push(CallFrame {
+			depth: self.stack.len() as u32 - 1,
+			kind,
+			caller,
+			callee,
+			code_hash: None,
+			value,
+			input: Bytes(input.to_vec()),
+			output: None,
+			reverted: false,
+			gas_used: 0,
+			error: None,
+			error_origin: None,
+			storage: Vec::new(),
+			events: Vec::new(),
+		});
+	}
+
+	/// Finish the currently executing frame.
+	pub(crate) fn exit(&mut self, result: Result<&ExecReturnValue, &ExecError>, gas_used: Weight) {
+		if let Some(frame) = self.current() {
+			frame.gas_used = gas_used;
+			match result {
+				Ok(output) => {
+					frame.output = Some(output.data.clone());
+					frame.reverted = output.flags.contains(ReturnFlags::REVERT);
+				},
+				Err(err) => {
+					frame.error = Some(err.error);
+					frame.error_origin = Some(err.origin);
+				},
+			}
+		}
+		self.stack.pop();
+	}
+
+	pub(crate) fn code_hash(&mut self, code_hash: CodeHash<T>) {
+		if let Some(frame) = self.current() {
+			frame.code_hash = Some(code_hash);
+		}
+	}
+
+	pub(crate) fn storage_read(&mut self, key: &StorageKey, value: Option<&[u8]>) {
+		if let Some(frame) = self.current() {
+			frame.storage.push(StorageAccess {
+				key: Bytes(key.to_vec()),
+				value: value.map(|v| Bytes(v.to_vec())),
+				previous: None,
+				write: false,
+			});
+		}
+	}
+
+	pub(crate) fn storage_write(
+		&mut self,
+		key: &StorageKey,
+		value: Option<Vec<u8>>,
+		previous: Option<Vec<u8>>,
+	) {
+		if let Some(frame) = self.current() {
+			frame.storage.push(StorageAccess {
+				key: Bytes(key.to_vec()),
+				value: value.map(Bytes),
+				previous: previous.map(Bytes),
+				write: true,
+			});
+		}
+	}
+
+	pub(crate) fn event(&mut self, topics: &[T::Hash], data: &[u8]) {
+		if let Some(frame) = self.current() {
+			frame.events.push(EmittedEvent {
+				topics: topics.to_vec(),
+				data: Bytes(data.to_vec()),
+			});
+		}
+	}
+
+	fn current(&mut self) -> Option<&mut CallFrameOf<T>> {
+		let index = *self.stack.last()?;
+		self.frames.get_mut(index)
+	}
+}
+
+environmental::environmental!(recorded_frames: Vec<Vec<u8>>);
+
+/// Execute `f` and return the frames of all contract executions it started.
+///
+/// This is what allows past extrinsics to be traced: They are replayed off-chain within this
+/// function. The executions are not altered by this as recording is never charged for.
+///
+/// Frames are kept SCALE encoded while `f` runs because the environment can't be generic
+/// over `T`.
+pub fn record_frames<T: Config, R>(f: impl FnOnce() -> R) -> (R, Vec<CallFrameOf<T>>) {
+	let mut recorded = Vec::new();
+	let result = recorded_frames::using(&mut recorded, f);
+	let frames = recorded
+		.iter()
+		.flat_map(|encoded| <Vec<CallFrameOf<T>>>::decode(&mut &encoded[..]).unwrap_or_default())
+		.collect();
+	(result, frames)
+}
+
+/// Whether the current execution happens within [`record_frames`].
+pub(crate) fn is_recording() -> bool {
+	recorded_frames::with(|_| ()).is_some()
+}
+
+/// Hand the frames of a finished top level execution to the enclosing [`record_frames`].
+pub(crate) fn record<T: Config>(tracer: Tracer<T>) {
+	recorded_frames::with(|recorded| recorded.push(tracer.into_frames().encode()));
+}
@@ -28,7 +28,7 @@
 //! Thus, before executing a contract it should be reinstrument with new schedule.
 
 use crate::{
-	CodeHash, CodeStorage, PristineCode, Schedule, Config, Error, Weight,
+	CodeHash, CodeMetadata, CodeStorage, PristineCode, Schedule, Config, Error, Weight,
 	wasm::{prepare, PrefabWasmModule}, Pallet as Contracts, Event,
 	gas::{GasMeter, Token},
 	weights::WeightInfo,
@@ -64,6 +64,39 @@ where
 	});
 }
 
+/// Put the instrumented module in storage without any contract using it.
+///
+/// The module is stored with a refcount of zero so that the first instantiation from it
+/// makes it one. It stays in storage until it is removed by [`remove_unused`] or its last
+/// user is gone.
+pub fn store_unused<T: Config>(mut prefab_module: PrefabWasmModule<T>)
+where
+	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>
+{
+	prefab_module.refcount = 0;
+	store(prefab_module);
+}
+
+/// Remove a module that is not used by any contract together with its pristine code and
+/// metadata.
+pub fn remove_unused<T: Config>(code_hash: CodeHash<T>) -> Result<(), DispatchError>
+where
+	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>
+{
+	<CodeStorage<T>>::try_mutate_exists(code_hash, |existing| {
+		match existing {
+			Some(module) if module.refcount == 0 => {
+				*existing = None;
+				<PristineCode<T>>::remove(code_hash);
+				<CodeMetadata<T>>::remove(code_hash);
+				Ok(())
+			}
+			Some(_) => Err(Error::<T>::CodeInUse.into()),
+			None => Err(Error::<T>::CodeNotFound.into()),
+		}
+	})
+}
+
 /// Decrement the refcount and store.
 ///
 /// Removes the code instead of storing it when the refcount drops to zero.
@@ -164,12 +197,13 @@ mod private {
 	}
 }
 
-/// Finish removal of a code by deleting the pristine code and emitting an event.
+/// Finish removal of a code by deleting the pristine code and its metadata and emitting an event.
 fn finish_removal<T: Config>(code_hash: CodeHash<T>)
 where
 	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>
 {
 	<PristineCode<T>>::remove(code_hash);
+	<CodeMetadata<T>>::remove(code_hash);
 	Contracts::<T>::deposit_event(Event::CodeRemoved(code_hash))
 }
 
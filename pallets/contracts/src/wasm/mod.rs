@@ -118,6 +118,18 @@ where
 		prepare::prepare_contract(original_code, schedule).map_err(Into::into)
 	}
 
+	/// Store the module without adding a user to it.
+	///
+	/// This is used to upload code ahead of instantiation.
+	pub fn store_unused(self) {
+		code_cache::store_unused(self)
+	}
+
+	/// Remove the code stored under `code_hash` if no contract is using it.
+	pub fn remove_unused(code_hash: CodeHash<T>) -> Result<(), DispatchError> {
+		code_cache::remove_unused::<T>(code_hash)
+	}
+
 	/// Create and store the module without checking nor instrumenting the passed code.
 	///
 	/// # Note
@@ -307,6 +319,8 @@ mod tests {
 		events: Vec<(Vec<H256>, Vec<u8>)>,
 		schedule: Schedule<Test>,
 		rent_params: RentParams<Test>,
+		// `None` if debug message recording is disabled
+		debug_buffer: Option<Vec<u8>>,
 	}
 
 	impl Ext for MockExt {
@@ -439,6 +453,14 @@ mod tests {
 		fn rent_params(&self) -> &RentParams<Self::T> {
 			&self.rent_params
 		}
+		fn append_debug_buffer(&mut self, msg: &str) -> bool {
+			if let Some(buffer) = &mut self.debug_buffer {
+				buffer.extend_from_slice(msg.as_bytes());
+				true
+			} else {
+				false
+			}
+		}
 	}
 
 	impl Ext for &mut MockExt {
@@ -544,6 +566,9 @@ mod tests {
 		fn rent_params(&self) -> &RentParams<Self::T> {
 			(**self).rent_params()
 		}
+		fn append_debug_buffer(&mut self, msg: &str) -> bool {
+			(**self).append_debug_buffer(msg)
+		}
 	}
 
 	fn execute<E: Ext>(
@@ -1985,4 +2010,88 @@ mod tests {
 		let rent_params = Bytes(<RentParams<Test>>::default().encode());
 		assert_eq!(output, ExecReturnValue { flags: ReturnFlags::empty(), data: rent_params });
 	}
+
+	const CODE_DEBUG_MESSAGE: &str = r#"
+(module
+	(import "seal0" "seal_debug_message" (func $seal_debug_message (param i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(data (i32.const 0) "Hello World!")
+
+	(func (export "call")
+		(call $seal_debug_message
+			(i32.const 0)	;; Pointer to the text buffer
+			(i32.const 12)	;; The size of the buffer
+		)
+		drop
+	)
+
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn debug_message_works() {
+		let mut ext = MockExt::default();
+		ext.debug_buffer = Some(Vec::new());
+		execute(
+			CODE_DEBUG_MESSAGE,
+			vec![],
+			&mut ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+
+		assert_eq!(std::str::from_utf8(&ext.debug_buffer.unwrap()).unwrap(), "Hello World!");
+	}
+
+	#[test]
+	fn debug_message_is_ignored_when_disabled() {
+		let mut ext = MockExt::default();
+		execute(
+			CODE_DEBUG_MESSAGE,
+			vec![],
+			&mut ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+
+		assert_eq!(ext.debug_buffer, None);
+	}
+
+	const CODE_DEBUG_MESSAGE_FAIL: &str = r#"
+(module
+	(import "seal0" "seal_debug_message" (func $seal_debug_message (param i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(data (i32.const 0) "\fc")
+
+	(func (export "call")
+		(call $seal_debug_message
+			(i32.const 0)	;; Pointer to the text buffer
+			(i32.const 1)	;; The size of the buffer
+		)
+		drop
+	)
+
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn debug_message_invalid_utf8_fails() {
+		let mut ext = MockExt::default();
+		ext.debug_buffer = Some(Vec::new());
+		let result = execute(
+			CODE_DEBUG_MESSAGE_FAIL,
+			vec![],
+			&mut ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		);
+		assert_eq!(
+			result,
+			Err(ExecError {
+				error: Error::<Test>::DebugMessageInvalidUTF8.into(),
+				origin: ErrorOrigin::Caller,
+			})
+		);
+	}
 }
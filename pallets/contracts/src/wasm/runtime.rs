@@ -72,6 +72,9 @@ pub enum ReturnCode {
 	/// The contract that was called is either no contract at all (a plain account)
 	/// or is a tombstone.
 	NotCallable = 8,
+	/// The call to `seal_debug_message` had no effect because debug message
+	/// recording was disabled.
+	LoggingDisabled = 9,
 }
 
 impl ConvertibleToWasm for ReturnCode {
@@ -218,6 +221,11 @@ pub enum RuntimeToken {
 	CopyIn(u32),
 	/// Weight of calling `seal_rent_params`.
 	RentParams,
+	/// Weight of calling `seal_debug_message`.
+	///
+	/// The message itself is only read from the sandbox when recording is enabled and then
+	/// charged as `CopyIn`.
+	DebugMessage,
 }
 
 impl<T: Config> Token<T> for RuntimeToken
@@ -287,6 +295,7 @@ where
 			ChainExtension(amount) => amount,
 			CopyIn(len) => s.return_per_byte.saturating_mul(len.into()),
 			RentParams => s.rent_params,
+			DebugMessage => s.debug_message,
 		}
 	}
 }
@@ -1413,6 +1422,37 @@ define_env!(Env, <E: Ext>,
 		Ok(())
 	},
 
+	// Emit a custom debug message.
+	//
+	// The message is appended to the debug buffer which is then supplied to the caller
+	// of the RPC dry run (`bare_call` or `bare_instantiate`). No newline is added.
+	// The message must be UTF-8 encoded. Messages that do not fit into the buffer
+	// anymore are silently dropped.
+	//
+	// This is a no-op when executing on-chain: The message is not even read from
+	// the sandbox memory in that case.
+	//
+	// # Return Value
+	//
+	// Returns `ReturnCode::Success` when debug message recording is enabled and
+	// `ReturnCode::LoggingDisabled` otherwise.
+	//
+	// # Traps
+	//
+	// - The message is not valid UTF-8 (only checked when recording is enabled).
+	[seal0] seal_debug_message(ctx, str_ptr: u32, str_len: u32) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::DebugMessage)?;
+		if ctx.ext.append_debug_buffer("") {
+			ctx.charge_gas(RuntimeToken::CopyIn(str_len))?;
+			let data = ctx.read_sandbox_memory(str_ptr, str_len)?;
+			let msg = core::str::from_utf8(&data)
+				.map_err(|_| Error::<E::T>::DebugMessageInvalidUTF8)?;
+			ctx.ext.append_debug_buffer(msg);
+			return Ok(ReturnCode::Success);
+		}
+		Ok(ReturnCode::LoggingDisabled)
+	},
+
 	// Stores the current block number of the current contract into the supplied buffer.
 	//
 	// The value is stored to linear memory at the address pointed to by `out_ptr`.
@@ -0,0 +1,13 @@
+;; Emits a debug message on every call
+(module
+	(import "seal0" "seal_debug_message" (func $seal_debug_message (param i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(data (i32.const 0) "Hello World!")
+
+	(func (export "deploy"))
+
+	(func (export "call")
+		(drop (call $seal_debug_message (i32.const 0) (i32.const 12)))
+	)
+)
@@ -0,0 +1,15 @@
+;; Reverts every call and returns four bytes of data
+(module
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(data (i32.const 0) "\01\02\03\04")
+
+	(func (export "deploy"))
+
+	(func (export "call")
+		;; flags: REVERT, data_ptr, data_len
+		(call $seal_return (i32.const 1) (i32.const 0) (i32.const 4))
+		(unreachable)
+	)
+)
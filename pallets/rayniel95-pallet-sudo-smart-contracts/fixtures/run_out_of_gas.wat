@@ -0,0 +1,9 @@
+;; Loops until the gas limit of the call is exhausted
+(module
+	(import "env" "memory" (memory 1 1))
+	(func (export "call")
+		(loop $inf (br $inf)) ;; just run out of gas
+		(unreachable)
+	)
+	(func (export "deploy"))
+)
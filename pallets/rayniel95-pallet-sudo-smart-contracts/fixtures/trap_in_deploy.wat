@@ -0,0 +1,8 @@
+;; Traps in its constructor so that it can never be instantiated
+(module
+	(import "env" "memory" (memory 1 1))
+	(func (export "deploy")
+		(unreachable)
+	)
+	(func (export "call"))
+)
@@ -0,0 +1,414 @@
+//! Verification of the contract storage proofs returned by `contracts_getStorageProof`.
+//!
+//! A [`ContractStorageProof`] is self-contained: Given the GRANDPA authority set that finalized
+//! its block, [`verify`] checks the value a contract stores under a key without trusting the
+//! node that served the proof. This takes three steps:
+//!
+//! 1. The justification must finalize the header and carry valid precommits of more than two
+//!    thirds of the weight of the authority set.
+//! 2. The contract info proof must prove the `ContractInfoOf` entry of the contract and the root
+//!    of its child trie against the state root of the header.
+//! 3. The storage proof must prove the value under the key against the root of the child trie.
+//!
+//! The crate is `no_std` so that the verifier can also run within a runtime.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+use finality_grandpa::{voter_set::VoterSet, BlockNumberOps, Chain};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_core::{storage::ChildInfo, Bytes};
+use sp_finality_grandpa::{AuthorityId, AuthoritySignature};
+use sp_io::hashing::{blake2_256, twox_128, twox_64};
+use sp_runtime::{traits::Header as HeaderT, RuntimeDebug};
+use sp_std::{
+	collections::{btree_map::BTreeMap, btree_set::BTreeSet},
+	prelude::*,
+};
+use sp_trie::{Layout, MemoryDB, StorageProof};
+
+pub use sp_finality_grandpa::{AuthorityList, SetId};
+
+/// The name of the contracts pallet in `construct_runtime!` which prefixes its storage keys.
+pub const CONTRACTS_PALLET: &[u8] = b"Contracts";
+
+/// A GRANDPA commit as contained in a justification.
+pub type Commit<Header> = finality_grandpa::Commit<
+	<Header as HeaderT>::Hash,
+	<Header as HeaderT>::Number,
+	AuthoritySignature,
+	AuthorityId,
+>;
+
+/// A value stored by a contract together with everything needed to verify it.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct ContractStorageProof<Header> {
+	/// The finalized header whose state is proven.
+	pub header: Header,
+	/// The SCALE encoded GRANDPA justification which finalized `header`.
+	pub justification: Bytes,
+	/// The nodes of the main trie proving the `ContractInfoOf` entry of the contract and the
+	/// root of its child trie.
+	pub contract_info_proof: Vec<Bytes>,
+	/// The nodes of the child trie of the contract proving the value under the key.
+	pub storage_proof: Vec<Bytes>,
+}
+
+/// The reasons why a proof is rejected.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Error {
+	/// The authority set is empty or contains an authority without weight.
+	InvalidAuthoritySet,
+	/// The justification can't be decoded.
+	MalformedJustification,
+	/// The justification finalizes another block than the header.
+	JustificationTargetMismatch,
+	/// The precommits don't reach the supermajority of the authority set.
+	InsufficientPrecommits,
+	/// A precommit isn't signed by its authority for the round and the authority set.
+	InvalidSignature,
+	/// The justification lacks the ancestry of a precommit target or contains unused headers.
+	InvalidVotesAncestries,
+	/// A trie proof lacks nodes needed to read a key.
+	IncompleteProof,
+	/// There is no contract at the address.
+	ContractNotFound,
+	/// The contract is a tombstone and has no storage.
+	ContractIsTombstone,
+	/// The proven contract info or child trie root can't be decoded.
+	MalformedState,
+}
+
+/// Verifies `proof` and returns the value the contract at `address` stores under `key` in the
+/// block of `proof.header`, `None` if nothing is stored under it.
+///
+/// `authorities` and `set_id` must describe the GRANDPA authority set which finalized the block
+/// and come from a trusted source. `address` is the SCALE encoded account id of the contract
+/// and `key` the key as used by the contract.
+pub fn verify<Header>(
+	proof: &ContractStorageProof<Header>,
+	authorities: &AuthorityList,
+	set_id: SetId,
+	address: &[u8],
+	key: &[u8; 32],
+) -> Result<Option<Vec<u8>>, Error>
+where
+	Header: HeaderT,
+	Header::Number: BlockNumberOps,
+{
+	GrandpaJustification::<Header>::decode(&mut &proof.justification[..])
+		.map_err(|_| Error::MalformedJustification)?
+		.verify(&proof.header, authorities, set_id)?;
+
+	let state_root = proof.header.state_root();
+	let contract_info = read_value::<Header>(
+		&proof.contract_info_proof,
+		state_root,
+		&contract_info_key(address),
+	)?
+		.ok_or(Error::ContractNotFound)?;
+	let child_info = ChildInfo::new_default(&trie_id(&contract_info)?);
+	let child_root = read_value::<Header>(
+		&proof.contract_info_proof,
+		state_root,
+		&child_info.prefixed_storage_key().into_inner(),
+	)?
+		.ok_or(Error::IncompleteProof)?;
+	let child_root = <Header::Hash as Decode>::decode(&mut &child_root[..])
+		.map_err(|_| Error::MalformedState)?;
+	read_value::<Header>(&proof.storage_proof, &child_root, &blake2_256(key))
+}
+
+/// Returns the key of the `ContractInfoOf` entry of the contract at `address` in the main trie.
+///
+/// `address` is the SCALE encoded account id of the contract.
+pub fn contract_info_key(address: &[u8]) -> Vec<u8> {
+	[
+		&twox_128(CONTRACTS_PALLET)[..],
+		&twox_128(b"ContractInfoOf")[..],
+		&twox_64(address)[..],
+		address,
+	]
+		.concat()
+}
+
+/// A GRANDPA justification as it is stored by the node.
+///
+/// This is the layout used by the `sc-finality-grandpa` client which isn't available without
+/// `std`.
+#[derive(Encode, Decode)]
+pub struct GrandpaJustification<Header: HeaderT> {
+	pub round: u64,
+	pub commit: Commit<Header>,
+	pub votes_ancestries: Vec<Header>,
+}
+
+impl<Header> GrandpaJustification<Header>
+where
+	Header: HeaderT,
+	Header::Number: BlockNumberOps,
+{
+	/// Checks that the justification finalizes `header` with precommits of the authority set
+	/// `set_id` signed in the round of the justification.
+	pub fn verify(
+		&self,
+		header: &Header,
+		authorities: &AuthorityList,
+		set_id: SetId,
+	) -> Result<(), Error> {
+		if self.commit.target_hash != header.hash() ||
+			self.commit.target_number != *header.number()
+		{
+			return Err(Error::JustificationTargetMismatch);
+		}
+
+		let voters = VoterSet::new(authorities.iter().cloned())
+			.ok_or(Error::InvalidAuthoritySet)?;
+		let ancestry = AncestryChain::<Header>::new(&self.votes_ancestries);
+		match finality_grandpa::validate_commit(&self.commit, &voters, &ancestry) {
+			Ok(ref result) if result.is_valid() => {},
+			_ => return Err(Error::InsufficientPrecommits),
+		}
+
+		let mut buf = Vec::new();
+		let mut visited = BTreeSet::new();
+		for signed in &self.commit.precommits {
+			if !sp_finality_grandpa::check_message_signature_with_buffer(
+				&finality_grandpa::Message::Precommit(signed.precommit.clone()),
+				&signed.id,
+				&signed.signature,
+				self.round,
+				set_id,
+				&mut buf,
+			) {
+				return Err(Error::InvalidSignature);
+			}
+			if signed.precommit.target_hash == self.commit.target_hash {
+				continue;
+			}
+			let route = ancestry
+				.ancestry(self.commit.target_hash, signed.precommit.target_hash)
+				.map_err(|_| Error::InvalidVotesAncestries)?;
+			visited.insert(signed.precommit.target_hash);
+			visited.extend(route);
+		}
+
+		// Headers which no precommit needs would let the justification grow without bound.
+		let ancestries: BTreeSet<_> = self.votes_ancestries.iter()
+			.map(|header| header.hash())
+			.collect();
+		if visited != ancestries {
+			return Err(Error::InvalidVotesAncestries);
+		}
+		Ok(())
+	}
+}
+
+/// The chain formed by the `votes_ancestries` of a justification.
+struct AncestryChain<Header: HeaderT> {
+	ancestry: BTreeMap<Header::Hash, Header>,
+}
+
+impl<Header: HeaderT> AncestryChain<Header> {
+	fn new(ancestry: &[Header]) -> Self {
+		Self {
+			ancestry: ancestry.iter().map(|header| (header.hash(), header.clone())).collect(),
+		}
+	}
+}
+
+impl<Header> Chain<Header::Hash, Header::Number> for AncestryChain<Header>
+where
+	Header: HeaderT,
+	Header::Number: BlockNumberOps,
+{
+	fn ancestry(
+		&self,
+		base: Header::Hash,
+		block: Header::Hash,
+	) -> Result<Vec<Header::Hash>, finality_grandpa::Error> {
+		let mut route = Vec::new();
+		let mut current = block;
+		while current != base {
+			let header = self.ancestry.get(&current)
+				.ok_or(finality_grandpa::Error::NotDescendent)?;
+			current = *header.parent_hash();
+			route.push(current);
+		}
+		// The route ends with `base` which isn't part of it.
+		route.pop();
+		Ok(route)
+	}
+}
+
+/// Reads `key` from the trie with `root` using only the nodes of `proof`.
+fn read_value<Header: HeaderT>(
+	proof: &[Bytes],
+	root: &Header::Hash,
+	key: &[u8],
+) -> Result<Option<Vec<u8>>, Error> {
+	let proof = StorageProof::new(proof.iter().map(|node| node.0.clone()).collect());
+	let db: MemoryDB<Header::Hashing> = proof.into();
+	sp_trie::read_trie_value::<Layout<Header::Hashing>, _>(&db, root, key)
+		.map_err(|_| Error::IncompleteProof)
+}
+
+/// Decodes the id of the child trie from an encoded `ContractInfo`.
+///
+/// Only the leading part is decoded which keeps the verifier independent of the types of the
+/// runtime: `Alive` is the first variant and the trie id the first field of its info.
+fn trie_id(mut contract_info: &[u8]) -> Result<Vec<u8>, Error> {
+	match u8::decode(&mut contract_info) {
+		Ok(0) => Vec::<u8>::decode(&mut contract_info).map_err(|_| Error::MalformedState),
+		Ok(1) => Err(Error::ContractIsTombstone),
+		_ => Err(Error::MalformedState),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::H256;
+	use sp_keyring::Ed25519Keyring::{self, Alice, Bob, Charlie};
+	use sp_runtime::{generic, traits::BlakeTwo256};
+	use sp_trie::{TrieDBMut, TrieMut};
+
+	type Header = generic::Header<u64, BlakeTwo256>;
+
+	const ADDRESS: [u8; 32] = [1; 32];
+	const KEY: [u8; 32] = [2; 32];
+	const SET_ID: SetId = 3;
+	const ROUND: u64 = 7;
+
+	fn authorities() -> AuthorityList {
+		vec![(Alice.public().into(), 1), (Bob.public().into(), 1), (Charlie.public().into(), 1)]
+	}
+
+	/// Builds a trie holding `entries` and returns its root together with all of its nodes.
+	fn trie(entries: &[(Vec<u8>, Vec<u8>)]) -> (H256, Vec<Bytes>) {
+		let mut db = MemoryDB::<BlakeTwo256>::default();
+		let mut root = H256::default();
+		{
+			let mut trie = TrieDBMut::<Layout<BlakeTwo256>>::new(&mut db, &mut root);
+			for (key, value) in entries {
+				trie.insert(key, value).unwrap();
+			}
+		}
+		let nodes = db.drain().into_iter().map(|(_, (node, _))| Bytes(node)).collect();
+		(root, nodes)
+	}
+
+	fn justification(header: &Header, signers: &[Ed25519Keyring]) -> Bytes {
+		let precommit = finality_grandpa::Precommit {
+			target_hash: header.hash(),
+			target_number: *header.number(),
+		};
+		let message = finality_grandpa::Message::Precommit(precommit.clone());
+		let payload = sp_finality_grandpa::localized_payload(ROUND, SET_ID, &message);
+		let precommits = signers.iter()
+			.map(|signer| finality_grandpa::SignedPrecommit {
+				precommit: precommit.clone(),
+				signature: signer.sign(&payload).into(),
+				id: signer.public().into(),
+			})
+			.collect();
+		let commit = Commit::<Header> {
+			target_hash: header.hash(),
+			target_number: *header.number(),
+			precommits,
+		};
+		Bytes(GrandpaJustification { round: ROUND, commit, votes_ancestries: Vec::new() }.encode())
+	}
+
+	/// Proves that the contract at `ADDRESS` with `contract_info` stores `value` under `KEY`.
+	fn proof(contract_info: Vec<u8>, value: &[u8]) -> ContractStorageProof<Header> {
+		let (child_root, storage_proof) = trie(&[(blake2_256(&KEY).to_vec(), value.to_vec())]);
+		let child_key = ChildInfo::new_default(b"trie").prefixed_storage_key().into_inner();
+		let (state_root, contract_info_proof) = trie(&[
+			(contract_info_key(&ADDRESS), contract_info),
+			(child_key, child_root.encode()),
+		]);
+		let header = Header::new(
+			1,
+			Default::default(),
+			state_root,
+			Default::default(),
+			Default::default(),
+		);
+		ContractStorageProof {
+			justification: justification(&header, &[Alice, Bob, Charlie]),
+			header,
+			contract_info_proof,
+			storage_proof,
+		}
+	}
+
+	/// The encoded info of an alive contract using the child trie `trie`.
+	fn alive() -> Vec<u8> {
+		(0u8, b"trie".to_vec(), 0u32, 0u32, H256::repeat_byte(5)).encode()
+	}
+
+	#[test]
+	fn verifies_value_of_finalized_block() {
+		let proof = proof(alive(), b"value");
+		assert_eq!(
+			verify(&proof, &authorities(), SET_ID, &ADDRESS, &KEY),
+			Ok(Some(b"value".to_vec())),
+		);
+		assert_eq!(verify(&proof, &authorities(), SET_ID, &ADDRESS, &[3; 32]), Ok(None));
+	}
+
+	#[test]
+	fn rejects_justification_not_matching_authority_set() {
+		let mut proof = proof(alive(), b"value");
+		assert_eq!(
+			verify(&proof, &authorities(), SET_ID + 1, &ADDRESS, &KEY),
+			Err(Error::InvalidSignature),
+		);
+		assert_eq!(
+			verify(&proof, &Vec::new(), SET_ID, &ADDRESS, &KEY),
+			Err(Error::InvalidAuthoritySet),
+		);
+
+		proof.justification = justification(&proof.header, &[Alice, Bob]);
+		assert_eq!(
+			verify(&proof, &authorities(), SET_ID, &ADDRESS, &KEY),
+			Err(Error::InsufficientPrecommits),
+		);
+
+		proof.header.number = 2;
+		assert_eq!(
+			verify(&proof, &authorities(), SET_ID, &ADDRESS, &KEY),
+			Err(Error::JustificationTargetMismatch),
+		);
+	}
+
+	#[test]
+	fn rejects_proofs_not_matching_state() {
+		// The storage proof of another value doesn't contain the root of the child trie.
+		let mut tampered = proof(alive(), b"value");
+		tampered.storage_proof = proof(alive(), b"other").storage_proof;
+		assert_eq!(
+			verify(&tampered, &authorities(), SET_ID, &ADDRESS, &KEY),
+			Err(Error::IncompleteProof),
+		);
+
+		let proof = proof(alive(), b"value");
+		assert_eq!(
+			verify(&proof, &authorities(), SET_ID, &[4; 32], &KEY),
+			Err(Error::ContractNotFound),
+		);
+	}
+
+	#[test]
+	fn rejects_tombstones() {
+		let proof = proof((1u8, H256::repeat_byte(5)).encode(), b"value");
+		assert_eq!(
+			verify(&proof, &authorities(), SET_ID, &ADDRESS, &KEY),
+			Err(Error::ContractIsTombstone),
+		);
+	}
+}
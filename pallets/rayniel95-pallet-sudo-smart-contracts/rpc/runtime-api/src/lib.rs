@@ -0,0 +1,121 @@
+//! Runtime API definition required by the sudo smart contracts RPC extensions.
+//!
+//! This API should be imported and implemented by the runtime of a node that wants to use the
+//! custom RPC extension adding the wrapper pallet's quota and name registry methods or that
+//! wants to read the pause state, the metadata or the storage usage of contracts or to trace
+//! their execution.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_runtime::{ApplyExtrinsicResult, traits::Block as BlockT};
+use sp_std::vec::Vec;
+pub use pallet_contracts::{
+	CallFrame, CallKind, EmittedEvent, ErrorOrigin, StorageAccess, StorageUsage,
+};
+pub use pallet_contracts_primitives::{ContractAccessError, ContractExecResult};
+pub use rayniel95_pallet_sudo_smart_contracts::{RemainingQuota, ResolvedName};
+
+sp_api::decl_runtime_apis! {
+	/// The API to interact with the wrapper pallet.
+	pub trait SudoContractsApi<AccountId, Balance, BlockNumber, Hash> where
+		AccountId: Codec,
+		Balance: Codec,
+		BlockNumber: Codec,
+		Hash: Codec,
+	{
+		/// Returns what is left of the execution quota of `who` in its current period.
+		///
+		/// Returns `None` if `who` is not limited by any quota.
+		fn remaining_quota(who: AccountId) -> Option<RemainingQuota<BlockNumber>>;
+
+		/// Returns whether calls to the contract at `address` are rejected because it is paused
+		/// or because the execution of all contracts is halted.
+		fn is_paused(address: AccountId) -> bool;
+
+		/// Returns whether the execution of all contracts is halted.
+		fn is_halted() -> bool;
+
+		/// Returns the nonce the next call relayed for `signer` must carry.
+		fn relay_nonce(signer: AccountId) -> u64;
+
+		/// Resolves a registered contract name like `org/payments@v2` or `org/payments`.
+		fn resolve(name: Vec<u8>) -> Option<ResolvedName<AccountId, Hash, BlockNumber>>;
+
+		/// Returns the name and version last published for the contract at `address`.
+		fn name_of(address: AccountId) -> Option<Vec<u8>>;
+
+		/// Returns the ink! metadata attached to the code `code_hash`.
+		fn code_metadata(code_hash: Hash) -> Option<Vec<u8>>;
+
+		/// Returns the ink! metadata attached to the current code of the contract at `address`.
+		fn contract_metadata(address: AccountId) -> Option<Vec<u8>>;
+
+		/// Returns the storage occupied by the contract at `address` and its storage limit.
+		fn storage_usage(address: AccountId) -> Option<StorageUsage>;
+
+		/// Lists up to `limit` keys of the child trie of the contract at `address` which start
+		/// with `prefix`, starting after `start_key` if supplied.
+		///
+		/// These are the hashed keys as stored in the trie. At most 1000 keys are returned.
+		fn storage_keys(
+			address: AccountId,
+			prefix: Vec<u8>,
+			start_key: Option<Vec<u8>>,
+			limit: u32,
+		) -> Result<Vec<Vec<u8>>, ContractAccessError>;
+
+		/// Returns the values stored by the contract at `address` under each of `keys`.
+		///
+		/// Only the first 1000 keys are read.
+		fn get_storage_many(
+			address: AccountId,
+			keys: Vec<[u8; 32]>,
+		) -> Result<Vec<Option<Vec<u8>>>, ContractAccessError>;
+
+		/// Lists up to `limit` key-value pairs of the child trie of the contract at `address`,
+		/// starting after `start_key` if supplied.
+		///
+		/// The keys are hashed like those of `storage_keys`. At most 1000 pairs are returned.
+		fn storage_entries(
+			address: AccountId,
+			start_key: Option<Vec<u8>>,
+			limit: u32,
+		) -> Result<Vec<(Vec<u8>, Vec<u8>)>, ContractAccessError>;
+
+		/// Returns the code hash of the contract at `address` if it is alive.
+		fn code_hash(address: AccountId) -> Option<Hash>;
+
+		/// Returns the key of the `ContractInfoOf` entry of the contract at `address` in the
+		/// main trie and the id of its child trie.
+		fn storage_proof_keys(
+			address: AccountId,
+		) -> Result<(Vec<u8>, Vec<u8>), ContractAccessError>;
+
+		/// Dry-runs a call of `dest` like `ContractsApi::call` and records the call tree.
+		///
+		/// The frames are returned in the order in which they were entered.
+		fn trace_call(
+			origin: AccountId,
+			dest: AccountId,
+			value: Balance,
+			gas_limit: u64,
+			input_data: Vec<u8>,
+		) -> (ContractExecResult, Vec<CallFrame<AccountId, Balance, Hash>>);
+
+		/// Replays the block of `header` on the state of its parent up to the last of
+		/// `extrinsics` and traces the contract executions of that last extrinsic.
+		///
+		/// `extrinsics` must be the leading extrinsics of the block. Returns the outcome of the
+		/// last extrinsic, the frames of all contract executions it started and the SCALE encoded
+		/// `EventRecord`s it deposited. Returns `None` if `extrinsics` is empty.
+		fn trace_extrinsic(
+			header: <Block as BlockT>::Header,
+			extrinsics: Vec<<Block as BlockT>::Extrinsic>,
+		) -> Option<(
+			ApplyExtrinsicResult,
+			Vec<CallFrame<AccountId, Balance, Hash>>,
+			Vec<Vec<u8>>,
+		)>;
+	}
+}
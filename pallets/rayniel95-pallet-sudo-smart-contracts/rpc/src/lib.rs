@@ -0,0 +1,808 @@
+//! Node-specific RPC methods for interaction with the sudo smart contracts pallet.
+
+use std::{cmp::Ordering, collections::VecDeque, convert::TryFrom, sync::Arc};
+
+use codec::Codec;
+use jsonrpc_core::{Error, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use serde::{Deserialize, Serialize};
+use sc_client_api::{BlockBackend, ProofProvider};
+use sc_rpc_api::DenyUnsafe;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{hashing::blake2_256, storage::ChildInfo, Bytes, H256};
+use sp_finality_grandpa::GRANDPA_ENGINE_ID;
+use sp_rpc::number::NumberOrHex;
+use sp_runtime::{
+	ApplyExtrinsicResult,
+	generic::BlockId,
+	traits::{Block as BlockT, Header as HeaderT, Zero},
+};
+
+pub use rayniel95_pallet_sudo_smart_contracts_proof::ContractStorageProof;
+pub use rayniel95_pallet_sudo_smart_contracts_rpc_runtime_api::{
+	CallFrame, ContractAccessError, ContractExecResult, RemainingQuota, ResolvedName, StorageUsage,
+	SudoContractsApi as SudoContractsRuntimeApi,
+};
+
+const RUNTIME_ERROR: i64 = 1;
+const INVALID_PARAMS: i64 = 2;
+const CONTRACT_DOESNT_EXIST: i64 = 3;
+const CONTRACT_IS_A_TOMBSTONE: i64 = 4;
+
+/// The maximum number of keys returned by a single `contracts_storageKeys` or
+/// `contracts_storageDiff` request and read by a single `contracts_getStorageMany` request.
+const MAX_STORAGE_KEYS: u32 = 1000;
+
+/// The maximum number of keys compared by a single `contracts_storageDiff` request.
+///
+/// This bounds the time a request takes when only few keys of a large storage changed. The
+/// page then holds less than the requested number of changes but is still followed by another.
+const MAX_STORAGE_DIFF_SCAN: usize = 10_000;
+
+/// A call to be dry run and traced by `contracts_traceCall`.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+pub struct TraceCallRequest<AccountId> {
+	origin: AccountId,
+	dest: AccountId,
+	value: NumberOrHex,
+	gas_limit: NumberOrHex,
+	input_data: Bytes,
+}
+
+/// A frame of the call tree together with the frames of the calls it made.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallTrace<AccountId, Balance, Hash> {
+	#[serde(flatten)]
+	pub frame: CallFrame<AccountId, Balance, Hash>,
+	pub calls: Vec<CallTrace<AccountId, Balance, Hash>>,
+}
+
+impl<AccountId, Balance, Hash> CallTrace<AccountId, Balance, Hash> {
+	/// Builds the trees from frames listed in the order in which they were entered.
+	///
+	/// Returns one tree for every top level execution.
+	pub fn from_frames(frames: Vec<CallFrame<AccountId, Balance, Hash>>) -> Vec<Self> {
+		let mut roots = Vec::new();
+		let mut stack: Vec<Self> = Vec::new();
+		let mut close = |stack: &mut Vec<Self>| {
+			let finished = stack.pop().expect("only called on a non empty stack; qed");
+			match stack.last_mut() {
+				Some(parent) => parent.calls.push(finished),
+				None => roots.push(finished),
+			}
+		};
+		for frame in frames {
+			while stack.len() > frame.depth as usize {
+				close(&mut stack);
+			}
+			stack.push(CallTrace { frame, calls: Vec::new() });
+		}
+		while !stack.is_empty() {
+			close(&mut stack);
+		}
+		roots
+	}
+}
+
+/// The result of `contracts_traceCall`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceCallResult<AccountId, Balance, Hash> {
+	/// The result of the dry run as it would be returned by `contracts_call`.
+	pub result: ContractExecResult,
+	/// The call tree. `null` if the call was rejected before any contract was called.
+	pub trace: Option<CallTrace<AccountId, Balance, Hash>>,
+}
+
+/// The result of `contracts_traceExtrinsic`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtrinsicTrace<AccountId, Balance, Hash> {
+	/// The outcome of applying the extrinsic.
+	pub result: ApplyExtrinsicResult,
+	/// One call tree for every contract execution started by the extrinsic.
+	pub calls: Vec<CallTrace<AccountId, Balance, Hash>>,
+	/// The SCALE encoded `EventRecord`s deposited by the extrinsic.
+	pub events: Vec<Bytes>,
+}
+
+/// A key whose value differs between the blocks compared by `contracts_storageDiff`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageChange {
+	/// The hashed key as stored in the child trie of the contract.
+	pub key: Bytes,
+	/// The value at the first block. `null` for added keys.
+	pub old_value: Option<Bytes>,
+	/// The value at the second block. `null` for removed keys.
+	pub new_value: Option<Bytes>,
+}
+
+/// The result of `contracts_storageDiff`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageDiff<Hash> {
+	pub added: Vec<StorageChange>,
+	pub removed: Vec<StorageChange>,
+	pub modified: Vec<StorageChange>,
+	/// The code hash of the contract at the first block. `null` if it wasn't alive.
+	pub old_code_hash: Option<Hash>,
+	/// The code hash of the contract at the second block. `null` if it isn't alive.
+	pub new_code_hash: Option<Hash>,
+	/// Whether the contract alive at the first block was removed or turned into a tombstone
+	/// in between. If it was instantiated again, its new storage is compared to the old one.
+	pub deleted: bool,
+	/// The key to pass as `startKey` to get the next page. `null` on the last page.
+	pub next_start_key: Option<Bytes>,
+}
+
+/// The storage of a contract at one block, fetched from the runtime in pages as it is read.
+struct StorageEntries<F> {
+	fetch: F,
+	page: VecDeque<(Vec<u8>, Vec<u8>)>,
+	last_key: Option<Vec<u8>>,
+	exhausted: bool,
+}
+
+impl<F> StorageEntries<F>
+where
+	F: FnMut(Option<Vec<u8>>) -> Result<Vec<(Vec<u8>, Vec<u8>)>>,
+{
+	/// Reads the entries after `start_key` using `fetch` which returns up to `MAX_STORAGE_KEYS`
+	/// entries after a key. Nothing is read if `alive` is `false`.
+	fn new(alive: bool, start_key: Option<Vec<u8>>, fetch: F) -> Self {
+		Self { fetch, page: VecDeque::new(), last_key: start_key, exhausted: !alive }
+	}
+
+	/// Returns the next entry without consuming it.
+	fn peek(&mut self) -> Result<Option<&(Vec<u8>, Vec<u8>)>> {
+		if self.page.is_empty() && !self.exhausted {
+			let page = (self.fetch)(self.last_key.clone())?;
+			self.exhausted = page.len() < MAX_STORAGE_KEYS as usize;
+			if let Some((key, _)) = page.last() {
+				self.last_key = Some(key.clone());
+			}
+			self.page = page.into();
+		}
+		Ok(self.page.front())
+	}
+
+	/// Consumes the entry returned by the last `peek`.
+	fn next(&mut self) -> (Vec<u8>, Vec<u8>) {
+		self.page.pop_front().expect("only called after `peek` returned an entry; qed")
+	}
+}
+
+/// Sudo contracts RPC methods.
+#[rpc]
+pub trait SudoContractsApi<BlockHash, Header, AccountId, Balance, BlockNumber, Hash> {
+	/// Returns what is left of the execution quota of `who` in its current period.
+	///
+	/// Returns `null` if `who` is not limited by any quota.
+	#[rpc(name = "sudoContracts_remainingQuota")]
+	fn remaining_quota(
+		&self,
+		who: AccountId,
+		at: Option<BlockHash>,
+	) -> Result<Option<RemainingQuota<BlockNumber>>>;
+
+	/// Returns the nonce the next call relayed for `signer` must carry.
+	#[rpc(name = "sudoContracts_relayNonce")]
+	fn relay_nonce(&self, signer: AccountId, at: Option<BlockHash>) -> Result<u64>;
+
+	/// Resolves a registered contract name like `org/payments@v2` or `org/payments`.
+	///
+	/// Returns `null` if the name or the version is not registered or if the contract of the
+	/// version no longer exists.
+	#[rpc(name = "contracts_resolve")]
+	fn resolve(
+		&self,
+		name: String,
+		at: Option<BlockHash>,
+	) -> Result<Option<ResolvedName<AccountId, Hash, BlockNumber>>>;
+
+	/// Returns the name and version last published for the contract at `address`, e.g.
+	/// `org/payments@v2`.
+	#[rpc(name = "contracts_nameOf")]
+	fn name_of(&self, address: AccountId, at: Option<BlockHash>) -> Result<Option<String>>;
+
+	/// Returns the ink! metadata attached to the code `code_hash`, as it was uploaded.
+	///
+	/// Returns `null` if the code is unknown or has no metadata.
+	#[rpc(name = "contracts_codeMetadata")]
+	fn code_metadata(&self, code_hash: Hash, at: Option<BlockHash>) -> Result<Option<Bytes>>;
+
+	/// Returns the ink! metadata attached to the current code of the contract at `address`, as
+	/// it was uploaded.
+	///
+	/// Returns `null` if there is no contract at `address` or its code has no metadata.
+	#[rpc(name = "contracts_contractMetadata")]
+	fn contract_metadata(&self, address: AccountId, at: Option<BlockHash>)
+		-> Result<Option<Bytes>>;
+
+	/// Returns the bytes and items stored by the contract at `address` together with the
+	/// storage limit it is subject to.
+	///
+	/// Returns `null` if there is no contract at `address`.
+	#[rpc(name = "contracts_storageUsage")]
+	fn storage_usage(&self, address: AccountId, at: Option<BlockHash>)
+		-> Result<Option<StorageUsage>>;
+
+	/// Lists up to `count` keys of the child trie of the contract at `address` which start
+	/// with `prefix`, in order and starting after `start_key` if supplied.
+	///
+	/// The keys are the blake2_256 hashes of the keys used by the contract. At most 1000 keys
+	/// are returned per request.
+	#[rpc(name = "contracts_storageKeys")]
+	fn storage_keys(
+		&self,
+		address: AccountId,
+		prefix: Option<Bytes>,
+		count: u32,
+		start_key: Option<Bytes>,
+		at: Option<BlockHash>,
+	) -> Result<Vec<Bytes>>;
+
+	/// Returns the values stored by the contract at `address` under each of `keys`, like
+	/// `contracts_getStorage` does for a single key.
+	///
+	/// At most 1000 keys can be read at once. Requests with more keys are rejected.
+	#[rpc(name = "contracts_getStorageMany")]
+	fn get_storage_many(
+		&self,
+		address: AccountId,
+		keys: Vec<H256>,
+		at: Option<BlockHash>,
+	) -> Result<Vec<Option<Bytes>>>;
+
+	/// Returns a proof of the value the contract at `address` stores under `key` which can be
+	/// checked with the `rayniel95-pallet-sudo-smart-contracts-proof` crate without trusting
+	/// this node.
+	///
+	/// The proof is made at `at` which must be a block the node stored a GRANDPA justification
+	/// for. If `at` is not supplied the latest such block is used, which is searched for among
+	/// the last `justification_period` finalized blocks.
+	#[rpc(name = "contracts_getStorageProof")]
+	fn storage_proof(
+		&self,
+		address: AccountId,
+		key: H256,
+		at: Option<BlockHash>,
+	) -> Result<ContractStorageProof<Header>>;
+
+	/// Compares the storage of the contract at `address` at the blocks `from` and `to` and
+	/// returns the keys which were added, removed or modified in between.
+	///
+	/// Up to `count` changes are returned per request, by key and starting after `start_key` if
+	/// supplied. At most 1000 changes are returned per request. The state of both blocks must
+	/// still be available which usually requires an archive node.
+	///
+	/// This is an unsafe method as it may read the whole storage of the contract twice.
+	#[rpc(name = "contracts_storageDiff")]
+	fn storage_diff(
+		&self,
+		address: AccountId,
+		from: BlockHash,
+		to: BlockHash,
+		count: Option<u32>,
+		start_key: Option<Bytes>,
+	) -> Result<StorageDiff<Hash>>;
+
+	/// Dry-runs a call like `contracts_call` and returns its call tree.
+	///
+	/// Every frame lists its caller, callee, code hash, input, output, value, used gas and
+	/// error together with the storage it accessed, the events it emitted and the calls it made.
+	///
+	/// This is an unsafe method as recording the trace is more expensive than a dry run.
+	#[rpc(name = "contracts_traceCall")]
+	fn trace_call(
+		&self,
+		call_request: TraceCallRequest<AccountId>,
+		at: Option<BlockHash>,
+	) -> Result<TraceCallResult<AccountId, Balance, Hash>>;
+
+	/// Re-executes the extrinsic at `index` of the block `block_hash` and returns the call
+	/// trees of its contract executions as they happened.
+	///
+	/// The preceding extrinsics of the block are replayed on the state of the parent block
+	/// first. The events of the extrinsic are returned SCALE encoded.
+	///
+	/// This is an unsafe method as it re-executes up to a whole block.
+	#[rpc(name = "contracts_traceExtrinsic")]
+	fn trace_extrinsic(
+		&self,
+		block_hash: BlockHash,
+		index: u32,
+	) -> Result<ExtrinsicTrace<AccountId, Balance, Hash>>;
+}
+
+/// An implementation of sudo contracts specific RPC methods.
+pub struct SudoContracts<C, B> {
+	client: Arc<C>,
+	deny_unsafe: DenyUnsafe,
+	justification_period: u32,
+	_marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> SudoContracts<C, B> {
+	/// Create new `SudoContracts` with the given reference to the client.
+	///
+	/// `deny_unsafe` decides whether the expensive tracing and diffing methods are served.
+	/// `justification_period` is the GRANDPA `justification_period` of the node.
+	pub fn new(client: Arc<C>, deny_unsafe: DenyUnsafe, justification_period: u32) -> Self {
+		SudoContracts {
+			client,
+			deny_unsafe,
+			justification_period,
+			_marker: Default::default(),
+		}
+	}
+}
+
+impl<C, Block> SudoContracts<C, Block>
+where
+	Block: BlockT,
+	C: HeaderBackend<Block> + BlockBackend<Block>,
+{
+	/// Returns the header of the block `hash`.
+	fn header(&self, hash: <Block as BlockT>::Hash) -> Result<<Block as BlockT>::Header> {
+		self.client.header(BlockId::hash(hash))
+			.map_err(client_error_into_rpc_err)?
+			.ok_or_else(|| invalid_params(format!("Block {:?} not found", hash)))
+	}
+
+	/// Returns the GRANDPA justification stored for the block `hash`.
+	///
+	/// Justifications are only stored for some finalized blocks: At least every
+	/// `justification_period` blocks and for every change of the authority set.
+	fn grandpa_justification(&self, hash: <Block as BlockT>::Hash) -> Result<Option<Vec<u8>>> {
+		let justifications = self.client.justifications(&BlockId::hash(hash))
+			.map_err(client_error_into_rpc_err)?;
+		Ok(justifications.and_then(|justifications| justifications.get(GRANDPA_ENGINE_ID).cloned()))
+	}
+
+	/// Returns the header and the GRANDPA justification of the latest finalized block a
+	/// justification is stored for.
+	///
+	/// Only the last `justification_period` finalized blocks are searched. One of them has a
+	/// justification unless the node imported them without one, e.g. while syncing.
+	fn latest_justified_header(&self) -> Result<(<Block as BlockT>::Header, Vec<u8>)> {
+		let mut hash = self.client.info().finalized_hash;
+		for _ in 0..self.justification_period {
+			let header = self.header(hash)?;
+			if let Some(justification) = self.grandpa_justification(hash)? {
+				return Ok((header, justification));
+			}
+			if header.number().is_zero() {
+				break;
+			}
+			hash = *header.parent_hash();
+		}
+		Err(invalid_params(format!(
+			"No GRANDPA justification is stored for the last {} finalized blocks",
+			self.justification_period,
+		)))
+	}
+}
+
+impl<C, Block, AccountId, Balance, BlockNumber, Hash>
+	SudoContractsApi<
+		<Block as BlockT>::Hash,
+		<Block as BlockT>::Header,
+		AccountId,
+		Balance,
+		BlockNumber,
+		Hash,
+	>
+	for SudoContracts<C, Block>
+where
+	Block: BlockT,
+	C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+	C: BlockBackend<Block> + ProofProvider<Block>,
+	C::Api: SudoContractsRuntimeApi<Block, AccountId, Balance, BlockNumber, Hash>,
+	AccountId: Codec,
+	Balance: Codec + TryFrom<NumberOrHex>,
+	BlockNumber: Codec,
+	Hash: Codec,
+{
+	fn remaining_quota(
+		&self,
+		who: AccountId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Option<RemainingQuota<BlockNumber>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash
+		));
+
+		api.remaining_quota(&at, who).map_err(runtime_error_into_rpc_err)
+	}
+
+	fn relay_nonce(
+		&self,
+		signer: AccountId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<u64> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash
+		));
+
+		api.relay_nonce(&at, signer).map_err(runtime_error_into_rpc_err)
+	}
+
+	fn resolve(
+		&self,
+		name: String,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Option<ResolvedName<AccountId, Hash, BlockNumber>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash
+		));
+
+		api.resolve(&at, name.into_bytes()).map_err(runtime_error_into_rpc_err)
+	}
+
+	fn name_of(
+		&self,
+		address: AccountId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Option<String>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash
+		));
+
+		let name = api.name_of(&at, address).map_err(runtime_error_into_rpc_err)?;
+		// Registered names are ASCII.
+		Ok(name.map(|name| String::from_utf8_lossy(&name).into_owned()))
+	}
+
+	fn code_metadata(
+		&self,
+		code_hash: Hash,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Option<Bytes>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash
+		));
+
+		api.code_metadata(&at, code_hash)
+			.map(|metadata| metadata.map(Bytes))
+			.map_err(runtime_error_into_rpc_err)
+	}
+
+	fn contract_metadata(
+		&self,
+		address: AccountId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Option<Bytes>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash
+		));
+
+		api.contract_metadata(&at, address)
+			.map(|metadata| metadata.map(Bytes))
+			.map_err(runtime_error_into_rpc_err)
+	}
+
+	fn storage_usage(
+		&self,
+		address: AccountId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Option<StorageUsage>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash
+		));
+
+		api.storage_usage(&at, address).map_err(runtime_error_into_rpc_err)
+	}
+
+	fn storage_keys(
+		&self,
+		address: AccountId,
+		prefix: Option<Bytes>,
+		count: u32,
+		start_key: Option<Bytes>,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Vec<Bytes>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash
+		));
+
+		let prefix = prefix.map(|prefix| prefix.0).unwrap_or_default();
+		let keys = api.storage_keys(
+			&at,
+			address,
+			prefix,
+			start_key.map(|key| key.0),
+			count.min(MAX_STORAGE_KEYS),
+		)
+			.map_err(runtime_error_into_rpc_err)?
+			.map_err(contract_access_error_into_rpc_err)?;
+		Ok(keys.into_iter().map(Bytes).collect())
+	}
+
+	fn get_storage_many(
+		&self,
+		address: AccountId,
+		keys: Vec<H256>,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Vec<Option<Bytes>>> {
+		if keys.len() > MAX_STORAGE_KEYS as usize {
+			return Err(invalid_params(
+				format!("At most {} keys can be read at once", MAX_STORAGE_KEYS)
+			));
+		}
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash
+		));
+
+		let keys = keys.into_iter().map(|key| key.to_fixed_bytes()).collect();
+		let values = api.get_storage_many(&at, address, keys)
+			.map_err(runtime_error_into_rpc_err)?
+			.map_err(contract_access_error_into_rpc_err)?;
+		Ok(values.into_iter().map(|value| value.map(Bytes)).collect())
+	}
+
+	fn storage_proof(
+		&self,
+		address: AccountId,
+		key: H256,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<ContractStorageProof<<Block as BlockT>::Header>> {
+		let api = self.client.runtime_api();
+		let (header, justification) = match at {
+			Some(hash) => {
+				let justification = self.grandpa_justification(hash)?
+					.ok_or_else(|| invalid_params(
+						format!("No GRANDPA justification is stored for block {:?}", hash),
+					))?;
+				(self.header(hash)?, justification)
+			},
+			None => self.latest_justified_header()?,
+		};
+		let at = BlockId::hash(header.hash());
+
+		let (contract_info_key, trie_id) = api.storage_proof_keys(&at, address)
+			.map_err(runtime_error_into_rpc_err)?
+			.map_err(contract_access_error_into_rpc_err)?;
+		let child_info = ChildInfo::new_default(&trie_id);
+		// The root of the child trie is stored in the main trie and proven alongside the info.
+		let child_root_key = child_info.prefixed_storage_key().into_inner();
+		let contract_info_proof = self.client
+			.read_proof(&at, &mut [&contract_info_key[..], &child_root_key[..]].iter().copied())
+			.map_err(client_error_into_rpc_err)?;
+		let storage_key = blake2_256(key.as_bytes());
+		let storage_proof = self.client
+			.read_child_proof(&at, &child_info, &mut std::iter::once(&storage_key[..]))
+			.map_err(client_error_into_rpc_err)?;
+
+		Ok(ContractStorageProof {
+			header,
+			justification: Bytes(justification),
+			contract_info_proof: contract_info_proof.iter_nodes().map(Bytes).collect(),
+			storage_proof: storage_proof.iter_nodes().map(Bytes).collect(),
+		})
+	}
+
+	fn storage_diff(
+		&self,
+		address: AccountId,
+		from: <Block as BlockT>::Hash,
+		to: <Block as BlockT>::Hash,
+		count: Option<u32>,
+		start_key: Option<Bytes>,
+	) -> Result<StorageDiff<Hash>> {
+		self.deny_unsafe.check_if_safe()?;
+		let api = self.client.runtime_api();
+		let (from, to) = (BlockId::hash(from), BlockId::hash(to));
+		let count = count.unwrap_or(MAX_STORAGE_KEYS).clamp(1, MAX_STORAGE_KEYS) as usize;
+		let start_key = start_key.map(|key| key.0);
+
+		let trie_id = |at: &BlockId<Block>| -> Result<_> {
+			Ok(api.storage_proof_keys(at, address.clone())
+				.map_err(runtime_error_into_rpc_err)?
+				.map(|(_, trie_id)| trie_id))
+		};
+		let (old_trie_id, new_trie_id) = match (trie_id(&from)?, trie_id(&to)?) {
+			(Err(_), Err(err)) => return Err(contract_access_error_into_rpc_err(err)),
+			(old_trie_id, new_trie_id) => (old_trie_id.ok(), new_trie_id.ok()),
+		};
+		let code_hash = |at: &BlockId<Block>| {
+			api.code_hash(at, address.clone()).map_err(runtime_error_into_rpc_err)
+		};
+		let mut diff = StorageDiff {
+			added: Vec::new(),
+			removed: Vec::new(),
+			modified: Vec::new(),
+			old_code_hash: code_hash(&from)?,
+			new_code_hash: code_hash(&to)?,
+			deleted: old_trie_id.is_some() && old_trie_id != new_trie_id,
+			next_start_key: None,
+		};
+
+		let entries = |at: &BlockId<Block>, start_key| -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+			api.storage_entries(at, address.clone(), start_key, MAX_STORAGE_KEYS)
+				.map_err(runtime_error_into_rpc_err)?
+				.map_err(contract_access_error_into_rpc_err)
+		};
+		let mut old = StorageEntries::new(
+			old_trie_id.is_some(),
+			start_key.clone(),
+			|start_key| entries(&from, start_key),
+		);
+		let mut new = StorageEntries::new(
+			new_trie_id.is_some(),
+			start_key,
+			|start_key| entries(&to, start_key),
+		);
+		// Both tries are iterated in key order side by side.
+		let (mut changes, mut scanned, mut last_key) = (0, 0, None);
+		loop {
+			let order = match (old.peek()?, new.peek()?) {
+				(None, None) => break,
+				(Some(_), None) => Ordering::Less,
+				(None, Some(_)) => Ordering::Greater,
+				(Some((old_key, _)), Some((new_key, _))) => old_key.cmp(new_key),
+			};
+			if changes >= count || scanned >= MAX_STORAGE_DIFF_SCAN {
+				diff.next_start_key = last_key.map(Bytes);
+				break;
+			}
+			scanned += 1;
+			let (key, change) = match order {
+				Ordering::Less => {
+					let (key, value) = old.next();
+					(key, Some((&mut diff.removed, Some(value), None)))
+				},
+				Ordering::Greater => {
+					let (key, value) = new.next();
+					(key, Some((&mut diff.added, None, Some(value))))
+				},
+				Ordering::Equal => {
+					let ((key, old_value), (_, new_value)) = (old.next(), new.next());
+					let change = if old_value != new_value {
+						Some((&mut diff.modified, Some(old_value), Some(new_value)))
+					} else {
+						None
+					};
+					(key, change)
+				},
+			};
+			if let Some((list, old_value, new_value)) = change {
+				list.push(StorageChange {
+					key: Bytes(key.clone()),
+					old_value: old_value.map(Bytes),
+					new_value: new_value.map(Bytes),
+				});
+				changes += 1;
+			}
+			last_key = Some(key);
+		}
+		Ok(diff)
+	}
+
+	fn trace_call(
+		&self,
+		call_request: TraceCallRequest<AccountId>,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<TraceCallResult<AccountId, Balance, Hash>> {
+		self.deny_unsafe.check_if_safe()?;
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash
+		));
+
+		let TraceCallRequest { origin, dest, value, gas_limit, input_data } = call_request;
+		let value: Balance = decode_number(value, "balance")?;
+		let gas_limit: u64 = decode_number(gas_limit, "gas limit")?;
+
+		let (result, frames) = api.trace_call(&at, origin, dest, value, gas_limit, input_data.0)
+			.map_err(runtime_error_into_rpc_err)?;
+		Ok(TraceCallResult { result, trace: CallTrace::from_frames(frames).pop() })
+	}
+
+	fn trace_extrinsic(
+		&self,
+		block_hash: <Block as BlockT>::Hash,
+		index: u32,
+	) -> Result<ExtrinsicTrace<AccountId, Balance, Hash>> {
+		self.deny_unsafe.check_if_safe()?;
+		let api = self.client.runtime_api();
+		let block = self.client.block(&BlockId::hash(block_hash))
+			.map_err(|e| Error {
+				code: ErrorCode::InternalError,
+				message: "Failed to read the block".into(),
+				data: Some(format!("{:?}", e).into()),
+			})?
+			.ok_or_else(|| invalid_params(format!("Block {:?} not found", block_hash)))?
+			.block;
+		let (mut header, mut extrinsics) = block.deconstruct();
+		if index as usize >= extrinsics.len() {
+			return Err(invalid_params(format!("Block has no extrinsic {}", index)));
+		}
+		extrinsics.truncate(index as usize + 1);
+		// The seal is removed on import before the block is executed.
+		header.digest_mut().logs.retain(|item| item.as_seal().is_none());
+		let at = BlockId::hash(*header.parent_hash());
+
+		let (result, frames, events) = api.trace_extrinsic(&at, header, extrinsics)
+			.map_err(runtime_error_into_rpc_err)?
+			.expect("at least one extrinsic is passed; qed");
+		Ok(ExtrinsicTrace {
+			result,
+			calls: CallTrace::from_frames(frames),
+			events: events.into_iter().map(Bytes).collect(),
+		})
+	}
+}
+
+/// Converts a failed access to a contract into an RPC error.
+fn contract_access_error_into_rpc_err(err: ContractAccessError) -> Error {
+	match err {
+		ContractAccessError::DoesntExist => Error {
+			code: ErrorCode::ServerError(CONTRACT_DOESNT_EXIST),
+			message: "The specified contract doesn't exist.".into(),
+			data: None,
+		},
+		ContractAccessError::IsTombstone => Error {
+			code: ErrorCode::ServerError(CONTRACT_IS_A_TOMBSTONE),
+			message: "The contract is a tombstone and doesn't have any storage.".into(),
+			data: None,
+		},
+	}
+}
+
+/// An RPC error for parameters that are well formed but do not refer to anything usable.
+fn invalid_params(message: String) -> Error {
+	Error {
+		code: ErrorCode::ServerError(INVALID_PARAMS),
+		message,
+		data: None,
+	}
+}
+
+/// Converts a number passed as parameter into the type expected by the runtime.
+fn decode_number<T: TryFrom<NumberOrHex>>(from: NumberOrHex, name: &str) -> Result<T> {
+	T::try_from(from)
+		.map_err(|_| invalid_params(format!("{:?} does not fit into the {} type", from, name)))
+}
+
+/// Converts a failure to read from the database of the node into an RPC error.
+fn client_error_into_rpc_err(err: impl std::fmt::Debug) -> Error {
+	Error {
+		code: ErrorCode::InternalError,
+		message: "Failed to read the chain".into(),
+		data: Some(format!("{:?}", err).into()),
+	}
+}
+
+/// Converts a runtime trap into an RPC error.
+fn runtime_error_into_rpc_err(err: impl std::fmt::Debug) -> Error {
+	Error {
+		code: ErrorCode::ServerError(RUNTIME_ERROR),
+		message: "Runtime error".into(),
+		data: Some(format!("{:?}", err).into()),
+	}
+}
@@ -0,0 +1,197 @@
+//! Access control lists that restrict who may call a contract through the wrapper.
+
+use codec::{Encode, Decode};
+use sp_runtime::RuntimeDebug;
+use sp_std::prelude::*;
+
+/// The first four bytes of the input data of an ink! message.
+pub type Selector = [u8; 4];
+
+/// The callers a contract accepts.
+///
+/// A contract without an entry in storage behaves as if it had [`AccessControl::AllowAll`].
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum AccessControl<AccountId> {
+	/// Every signed account may call every message of the contract.
+	AllowAll,
+	/// Only the listed accounts may call the contract, regardless of the message.
+	Accounts(Vec<AccountId>),
+	/// Only the listed messages may be called, each by its own list of accounts.
+	Selectors(Vec<(Selector, Vec<AccountId>)>),
+}
+
+impl<AccountId> Default for AccessControl<AccountId> {
+	fn default() -> Self {
+		Self::AllowAll
+	}
+}
+
+/// The rule of an [`AccessControl`] that rejected a call.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Rejection {
+	/// The caller is not in the account allowlist.
+	CallerNotAllowed,
+	/// The input data is too short to contain a selector.
+	MissingSelector,
+	/// The selector of the message is not in the list.
+	SelectorNotAllowed,
+	/// The caller is not allowed to call this particular message.
+	CallerNotAllowedForSelector,
+}
+
+/// An edit that does not fit the shape of the [`AccessControl`] it is applied to.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum EditError {
+	/// A per-message edit on an account list or vice versa.
+	Mismatch,
+	/// The account to revoke is not in the list.
+	NotGranted,
+}
+
+impl<AccountId: PartialEq> AccessControl<AccountId> {
+	/// Checks whether `caller` may call the contract with the given input `data`.
+	pub fn check(&self, caller: &AccountId, data: &[u8]) -> Result<(), Rejection> {
+		match self {
+			Self::AllowAll => Ok(()),
+			Self::Accounts(accounts) => if accounts.contains(caller) {
+				Ok(())
+			} else {
+				Err(Rejection::CallerNotAllowed)
+			},
+			Self::Selectors(rules) => {
+				let selector = selector_of(data).ok_or(Rejection::MissingSelector)?;
+				let (_, accounts) = rules.iter()
+					.find(|(s, _)| *s == selector)
+					.ok_or(Rejection::SelectorNotAllowed)?;
+				if accounts.contains(caller) {
+					Ok(())
+				} else {
+					Err(Rejection::CallerNotAllowedForSelector)
+				}
+			}
+		}
+	}
+
+	/// The number of accounts in all lists.
+	pub fn accounts_len(&self) -> u32 {
+		match self {
+			Self::AllowAll => 0,
+			Self::Accounts(accounts) => accounts.len() as u32,
+			Self::Selectors(rules) =>
+				rules.iter().map(|(_, accounts)| accounts.len() as u32).sum(),
+		}
+	}
+
+	/// Allows `who` to call the contract or, if `selector` is supplied, that message.
+	///
+	/// An [`AccessControl::AllowAll`] is turned into an empty list of the requested kind first.
+	pub fn grant(&mut self, who: AccountId, selector: Option<Selector>) -> Result<(), EditError> {
+		if let Self::AllowAll = self {
+			*self = match selector {
+				None => Self::Accounts(Vec::new()),
+				Some(_) => Self::Selectors(Vec::new()),
+			};
+		}
+		match (self, selector) {
+			(Self::Accounts(accounts), None) => if !accounts.contains(&who) {
+				accounts.push(who);
+			},
+			(Self::Selectors(rules), Some(selector)) => {
+				match rules.iter_mut().find(|(s, _)| *s == selector) {
+					Some((_, accounts)) => if !accounts.contains(&who) {
+						accounts.push(who);
+					},
+					None => rules.push((selector, vec![who])),
+				}
+			}
+			_ => return Err(EditError::Mismatch),
+		}
+		Ok(())
+	}
+
+	/// Removes the permission previously given by [`Self::grant`].
+	///
+	/// A message whose last account is revoked is removed from the list.
+	pub fn revoke(&mut self, who: &AccountId, selector: Option<Selector>) -> Result<(), EditError> {
+		match (self, selector) {
+			(Self::Accounts(accounts), None) => remove(accounts, who),
+			(Self::Selectors(rules), Some(selector)) => {
+				let index = rules.iter()
+					.position(|(s, _)| *s == selector)
+					.ok_or(EditError::NotGranted)?;
+				remove(&mut rules[index].1, who)?;
+				if rules[index].1.is_empty() {
+					rules.swap_remove(index);
+				}
+				Ok(())
+			}
+			_ => Err(EditError::Mismatch),
+		}
+	}
+}
+
+/// Extracts the ink! selector from the input data of a call.
+pub fn selector_of(data: &[u8]) -> Option<Selector> {
+	let mut selector = [0u8; 4];
+	selector.copy_from_slice(data.get(..4)?);
+	Some(selector)
+}
+
+fn remove<AccountId: PartialEq>(accounts: &mut Vec<AccountId>, who: &AccountId) -> Result<(), EditError> {
+	let index = accounts.iter().position(|a| a == who).ok_or(EditError::NotGranted)?;
+	accounts.swap_remove(index);
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const FLIP: Selector = [0xc0, 0x96, 0xa5, 0xf3];
+	const GET: Selector = [0x1e, 0x5c, 0xa4, 0x56];
+
+	#[test]
+	fn allow_all_accepts_everyone() {
+		let acl = AccessControl::<u64>::default();
+		assert_eq!(acl.check(&1, &[]), Ok(()));
+		assert_eq!(acl.check(&2, &FLIP), Ok(()));
+	}
+
+	#[test]
+	fn account_list_rejects_strangers() {
+		let mut acl = AccessControl::<u64>::AllowAll;
+		acl.grant(1, None).unwrap();
+		assert_eq!(acl, AccessControl::Accounts(vec![1]));
+		assert_eq!(acl.check(&1, &FLIP), Ok(()));
+		assert_eq!(acl.check(&2, &FLIP), Err(Rejection::CallerNotAllowed));
+
+		acl.revoke(&1, None).unwrap();
+		assert_eq!(acl.check(&1, &FLIP), Err(Rejection::CallerNotAllowed));
+		assert_eq!(acl.revoke(&1, None), Err(EditError::NotGranted));
+	}
+
+	#[test]
+	fn selector_list_checks_each_message() {
+		let mut acl = AccessControl::<u64>::AllowAll;
+		acl.grant(1, Some(FLIP)).unwrap();
+		acl.grant(2, Some(GET)).unwrap();
+		acl.grant(1, Some(GET)).unwrap();
+
+		assert_eq!(acl.check(&1, &[FLIP.to_vec(), vec![7]].concat()), Ok(()));
+		assert_eq!(acl.check(&2, &FLIP), Err(Rejection::CallerNotAllowedForSelector));
+		assert_eq!(acl.check(&2, &GET), Ok(()));
+		assert_eq!(acl.check(&1, &[0, 0, 0, 0]), Err(Rejection::SelectorNotAllowed));
+		assert_eq!(acl.check(&1, &[0xc0, 0x96]), Err(Rejection::MissingSelector));
+
+		acl.revoke(&1, Some(FLIP)).unwrap();
+		assert_eq!(acl.check(&1, &FLIP), Err(Rejection::SelectorNotAllowed));
+	}
+
+	#[test]
+	fn edits_must_match_the_list_kind() {
+		let mut acl = AccessControl::<u64>::Accounts(vec![1]);
+		assert_eq!(acl.grant(2, Some(FLIP)), Err(EditError::Mismatch));
+		assert_eq!(acl.revoke(&1, Some(FLIP)), Err(EditError::Mismatch));
+		assert_eq!(AccessControl::<u64>::AllowAll.revoke(&1, None), Err(EditError::Mismatch));
+	}
+}
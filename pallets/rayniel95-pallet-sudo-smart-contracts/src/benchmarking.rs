@@ -1,24 +1,344 @@
-//! Benchmarking setup for pallet-template
+//! Benchmarking setup for rayniel95-pallet-sudo-smart-contracts
+//!
+//! Extrinsics that execute contracts are benchmarked with the smallest possible contract or,
+//! where the code size is a component, with code that the contracts pallet rejects right away.
+//! This way the results cover the work of the wrapper while the weight of the execution
+//! itself is declared by `pallet_contracts`.
 
 use super::*;
 
 use frame_system::RawOrigin;
-use frame_benchmarking::{benchmarks, whitelisted_caller, impl_benchmark_test_suite};
+use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite, whitelisted_caller};
+use pallet_contracts::{Schedule, SchedulePreset};
+use sp_runtime::traits::Bounded;
+use sp_std::vec;
 #[allow(unused)]
-use crate::Pallet as Template;
+use crate::Pallet as SudoContracts;
+
+const GAS_LIMIT: Weight = 10_000_000_000;
+
+fn quota<T: Config>() -> Quota<T::BlockNumber> {
+	Quota {
+		max_weight: 100 * GAS_LIMIT,
+		max_calls: 100,
+		period: 100u32.into(),
+	}
+}
+
+/// Encodes `value` as unsigned LEB128 like the wasm binary format expects.
+fn leb128(mut value: u32) -> Vec<u8> {
+	let mut bytes = Vec::new();
+	loop {
+		let byte = (value & 0x7f) as u8;
+		value >>= 7;
+		if value == 0 {
+			bytes.push(byte);
+			return bytes;
+		}
+		bytes.push(byte | 0x80);
+	}
+}
+
+/// A contract whose `deploy` and `call` functions do nothing.
+///
+/// `call` is padded with `nops` nop instructions so that different paddings yield different
+/// code hashes.
+fn dummy_code(nops: u32) -> Vec<u8> {
+	let mut call_body = vec![0x00];
+	call_body.extend(sp_std::iter::repeat(0x01).take(nops as usize));
+	call_body.push(0x0b);
+	let mut code_section = vec![0x02];
+	code_section.extend(leb128(call_body.len() as u32));
+	code_section.extend(call_body);
+	code_section.extend(&[0x02, 0x00, 0x0b]);
+
+	let mut code = vec![
+		// magic and version
+		0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00,
+		// type section: () -> ()
+		0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+		// import section: (import "env" "memory" (memory 1 1))
+		0x02, 0x10, 0x01, 0x03, b'e', b'n', b'v', 0x06, b'm', b'e', b'm', b'o', b'r', b'y',
+		0x02, 0x01, 0x01, 0x01,
+		// function section: two functions of type 0
+		0x03, 0x03, 0x02, 0x00, 0x00,
+		// export section: "call" and "deploy"
+		0x07, 0x11, 0x02, 0x04, b'c', b'a', b'l', b'l', 0x00, 0x00,
+		0x06, b'd', b'e', b'p', b'l', b'o', b'y', 0x00, 0x01,
+		// code section
+		0x0a,
+	];
+	code.extend(leb128(code_section.len() as u32));
+	code.extend(code_section);
+	code
+}
+
+/// A funded account that is allowed to deploy contracts.
+fn deployer<T: Config>() -> T::AccountId
+where
+	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>,
+{
+	let deployer: T::AccountId = whitelisted_caller();
+	T::Currency::make_free_balance_be(&deployer, BalanceOf::<T>::max_value() / 2u32.into());
+	pallet_contracts::Pallet::<T>::add_deployer(RawOrigin::Root.into(), deployer.clone(), None)
+		.unwrap();
+	deployer
+}
+
+/// Stores and approves the code of [`dummy_code`] with the given padding.
+fn approved_code<T: Config>(deployer: &T::AccountId, nops: u32) -> CodeHash<T>
+where
+	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>,
+{
+	let code = dummy_code(nops);
+	let code_hash = T::Hashing::hash(&code);
+	pallet_contracts::Pallet::<T>::upload_code(RawOrigin::Signed(deployer.clone()).into(), code)
+		.unwrap();
+	pallet_contracts::Pallet::<T>::approve_code(RawOrigin::Root.into(), code_hash).unwrap();
+	code_hash
+}
+
+/// Instantiates [`dummy_code`] through the wrapper so that `deployer` owns the contract.
+fn dummy_contract<T: Config>(deployer: &T::AccountId) -> T::AccountId
+where
+	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>,
+{
+	let code = dummy_code(0);
+	let code_hash = T::Hashing::hash(&code);
+	SudoContracts::<T>::instantiate_with_code(
+		RawOrigin::Signed(deployer.clone()).into(),
+		endowment::<T>(),
+		GAS_LIMIT,
+		code,
+		vec![],
+		vec![],
+		None,
+	).unwrap();
+	let contract = pallet_contracts::Pallet::<T>::contract_address(deployer, &code_hash, &[]);
+	assert_eq!(SudoContracts::<T>::contract_owner(&contract).as_ref(), Some(deployer));
+	contract
+}
+
+/// A valid name of the maximum length.
+fn registry_name<T: Config>() -> Vec<u8> {
+	let mut name = b"org/".to_vec();
+	name.resize(T::MaxNameLength::get() as usize, b'a');
+	name
+}
+
+fn assert_last_event<T: Config>(generic_event: <T as Config>::Event) {
+	let events = frame_system::Pallet::<T>::events();
+	let system_event: <T as frame_system::Config>::Event = generic_event.into();
+	let frame_system::EventRecord { event, .. } = &events[events.len() - 1];
+	assert_eq!(event, &system_event);
+}
+
+fn endowment<T: Config>() -> BalanceOf<T> {
+	pallet_contracts::Pallet::<T>::subsistence_threshold().saturating_mul(100u32.into())
+}
 
 benchmarks! {
-	do_something {
-		let s in 0 .. 100;
+	where_clause { where
+		T::AccountId: UncheckedFrom<T::Hash>,
+		T::AccountId: AsRef<[u8]>,
+	}
+
+	call {
+		let caller = deployer::<T>();
+		let contract = dummy_contract::<T>(&caller);
+		<AccessControlLists<T>>::insert(&contract, AccessControl::Accounts(vec![caller.clone()]));
+		<DefaultQuota<T>>::put(quota::<T>());
+		let dest = T::Lookup::unlookup(contract);
+	}: _(RawOrigin::Signed(caller), dest, 0u32.into(), GAS_LIMIT, vec![])
+
+	batch_call {
+		let n in 1 .. T::MaxBatchedCalls::get();
+		let caller = deployer::<T>();
+		let contract = dummy_contract::<T>(&caller);
+		<AccessControlLists<T>>::insert(&contract, AccessControl::Accounts(vec![caller.clone()]));
+		<DefaultQuota<T>>::put(quota::<T>());
+		let calls = (0..n)
+			.map(|_| (T::Lookup::unlookup(contract.clone()), 0u32.into(), GAS_LIMIT, vec![]))
+			.collect::<Vec<_>>();
+	}: _(RawOrigin::Signed(caller.clone()), calls)
+	verify {
+		assert_last_event::<T>(Event::BatchCompleted(caller, n).into());
+	}
+
+	// The code is not valid wasm, so the contracts pallet rejects it before instrumenting it.
+	instantiate_with_code {
+		let c in 0 .. T::MaxCodeSize::get() / 1024;
+		let caller = deployer::<T>();
+		<DefaultQuota<T>>::put(quota::<T>());
+		let code = vec![0u8; c as usize * 1024];
+	}: _(RawOrigin::Signed(caller), endowment::<T>(), GAS_LIMIT, code, vec![], vec![], None)
+
+	instantiate {
+		let caller = deployer::<T>();
+		let code_hash = approved_code::<T>(&caller, 0);
+		<DefaultQuota<T>>::put(quota::<T>());
+		let contract = pallet_contracts::Pallet::<T>::contract_address(&caller, &code_hash, &[]);
+	}: _(RawOrigin::Signed(caller.clone()), endowment::<T>(), GAS_LIMIT, code_hash, vec![], vec![])
+	verify {
+		assert_eq!(ContractOwners::<T>::get(&contract), Some(caller));
+	}
+
+	update_schedule {
+		let caller = deployer::<T>();
+		let schedule = Schedule::<T>::from(SchedulePreset::LargeContracts)
+			.with_version(u32::max_value());
+	}: _(RawOrigin::Signed(caller), schedule)
+
+	// Instrumenting the code is weighed by the contracts pallet, so the size comes from the
+	// metadata which is only handled by the wrapper.
+	upload_code {
+		let c in 0 .. T::MaxMetadataLen::get() / 1024;
+		let caller = deployer::<T>();
+		<DefaultQuota<T>>::put(quota::<T>());
+		let metadata = vec![0u8; c as usize * 1024];
+	}: _(RawOrigin::Signed(caller), dummy_code(0), Some(metadata))
+
+	// The contract is not evictable, so the contracts pallet rejects the claim right away and
+	// the eviction itself is weighed by `pallet_contracts`.
+	claim_surcharge {
+		let contract = dummy_contract::<T>(&deployer::<T>());
+		let caller = pallet_sudo::Pallet::<T>::key();
+	}: {
+		let _ = SudoContracts::<T>::claim_surcharge(
+			RawOrigin::Signed(caller).into(), contract, None,
+		);
+	}
+
+	set_code {
+		let caller = deployer::<T>();
+		let contract = dummy_contract::<T>(&caller);
+		let code_hash = approved_code::<T>(&caller, 1);
+	}: _(RawOrigin::Signed(caller), contract.clone(), code_hash)
+	verify {
+		let old_code_hash = T::Hashing::hash(&dummy_code(0));
+		assert_last_event::<T>(Event::CodeUpgraded(contract, old_code_hash, code_hash).into());
+	}
+
+	set_default_quota {
+		let quota = quota::<T>();
+	}: _(RawOrigin::Root, Some(quota))
+	verify {
+		assert_eq!(DefaultQuota::<T>::get(), Some(quota));
+	}
+
+	set_account_quota {
+		let who: T::AccountId = account("who", 0, 0);
+		let quota = quota::<T>();
+	}: _(RawOrigin::Root, who.clone(), Some(quota))
+	verify {
+		assert_eq!(AccountQuotas::<T>::get(&who), Some(quota));
+	}
+
+	set_access_control {
+		let a in 0 .. 100;
 		let caller: T::AccountId = whitelisted_caller();
-	}: _(RawOrigin::Signed(caller), s)
+		let contract: T::AccountId = account("contract", 0, 0);
+		<ContractOwners<T>>::insert(&contract, &caller);
+		let acl = AccessControl::Accounts((0..a).map(|i| account("who", i, 0)).collect());
+	}: _(RawOrigin::Signed(caller), contract.clone(), acl.clone())
+	verify {
+		assert_eq!(AccessControlLists::<T>::get(&contract), acl);
+	}
+
+	grant_access {
+		let caller: T::AccountId = whitelisted_caller();
+		let contract: T::AccountId = account("contract", 0, 0);
+		let who: T::AccountId = account("who", 0, 0);
+		<ContractOwners<T>>::insert(&contract, &caller);
+	}: _(RawOrigin::Signed(caller), contract.clone(), who.clone(), None)
+	verify {
+		assert_eq!(AccessControlLists::<T>::get(&contract), AccessControl::Accounts(vec![who]));
+	}
+
+	revoke_access {
+		let caller: T::AccountId = whitelisted_caller();
+		let contract: T::AccountId = account("contract", 0, 0);
+		let who: T::AccountId = account("who", 0, 0);
+		<ContractOwners<T>>::insert(&contract, &caller);
+		<AccessControlLists<T>>::insert(&contract, AccessControl::Accounts(vec![who.clone()]));
+	}: _(RawOrigin::Signed(caller), contract.clone(), who, None)
+	verify {
+		assert_eq!(AccessControlLists::<T>::get(&contract), AccessControl::Accounts(vec![]));
+	}
+
+	schedule_call {
+		let origin: T::AccountId = account("origin", 0, 0);
+		let dest = T::Lookup::unlookup(account("contract", 0, 0));
+		let start = frame_system::Pallet::<T>::block_number() + 1u32.into();
+	}: _(RawOrigin::Root, origin, dest, GAS_LIMIT, vec![], start, Some(1u32.into()), None)
+	verify {
+		assert_eq!(Agenda::<T>::get(start), vec![0]);
+	}
+
+	add_relayer {
+		let relayer: T::AccountId = account("relayer", 0, 0);
+	}: _(RawOrigin::Root, relayer.clone())
+	verify {
+		assert!(SudoContracts::<T>::is_relayer(&relayer));
+	}
+
+	remove_relayer {
+		let relayer: T::AccountId = account("relayer", 0, 0);
+		<Relayers<T>>::insert(&relayer, true);
+	}: _(RawOrigin::Root, relayer.clone())
+	verify {
+		assert!(!SudoContracts::<T>::is_relayer(&relayer));
+	}
+
+	register_name {
+		let caller = deployer::<T>();
+		let contract = dummy_contract::<T>(&caller);
+		let name = registry_name::<T>();
+	}: _(RawOrigin::Root, name.clone(), caller.clone(), contract)
+	verify {
+		assert_eq!(Names::<T>::get(&name), Some(NameRecord { owner: caller, latest: 1 }));
+	}
+
+	publish_name_version {
+		let caller = deployer::<T>();
+		let contract = dummy_contract::<T>(&caller);
+		let name = registry_name::<T>();
+		SudoContracts::<T>::register_name(
+			RawOrigin::Root.into(), name.clone(), caller.clone(), contract.clone(),
+		)?;
+	}: _(RawOrigin::Signed(caller.clone()), name.clone(), contract)
+	verify {
+		assert_eq!(Names::<T>::get(&name), Some(NameRecord { owner: caller, latest: 2 }));
+	}
+
+	transfer_name {
+		let caller = deployer::<T>();
+		let contract = dummy_contract::<T>(&caller);
+		let name = registry_name::<T>();
+		let new_owner: T::AccountId = account("owner", 0, 0);
+		SudoContracts::<T>::register_name(
+			RawOrigin::Root.into(), name.clone(), caller.clone(), contract,
+		)?;
+	}: _(RawOrigin::Signed(caller), name.clone(), new_owner.clone())
+	verify {
+		assert_eq!(Names::<T>::get(&name), Some(NameRecord { owner: new_owner, latest: 1 }));
+	}
+
+	cancel_scheduled_call {
+		let origin: T::AccountId = account("origin", 0, 0);
+		let dest = T::Lookup::unlookup(account("contract", 0, 0));
+		let start = frame_system::Pallet::<T>::block_number() + 1u32.into();
+		SudoContracts::<T>::schedule_call(
+			RawOrigin::Root.into(), origin, dest, GAS_LIMIT, vec![], start, None, None,
+		)?;
+	}: _(RawOrigin::Root, 0)
 	verify {
-		assert_eq!(Something::<T>::get(), Some(s));
+		assert!(ScheduledCalls::<T>::get(0).is_none());
 	}
 }
 
 impl_benchmark_test_suite!(
-	Template,
+	SudoContracts,
 	crate::mock::new_test_ext(),
 	crate::mock::Test,
 );
@@ -1,21 +1,101 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(test)]
+mod mock;
 #[cfg(test)]
 mod tests;
-mod scheduler;
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+pub mod scheduler;
 pub mod weight_info;
+pub mod placeholder_weights;
+pub mod access_control;
+pub mod quota;
+pub mod relay;
+pub mod registry;
 
-use sp_core::crypto::UncheckedFrom;
+use codec::{Encode, Decode};
+use sp_core::{Bytes, crypto::UncheckedFrom};
 
-use sp_runtime::traits::StaticLookup;
-use frame_support::traits::Currency;
-use pallet_contracts::{Schedule};
+use sp_runtime::{
+	RuntimeDebug, TransactionOutcome,
+	traits::{Hash, IdentifyAccount, Saturating, StaticLookup, Verify, Zero},
+};
+use frame_support::{
+	dispatch::{DispatchResult, DispatchResultWithPostInfo},
+	storage::with_transaction,
+	traits::Currency,
+	weights::Weight,
+};
+use pallet_contracts::{CallFrameOf, Schedule};
+use pallet_contracts_primitives::{Code, ContractExecResult, ExecReturnValue, ReturnFlags};
+use access_control::{AccessControl, EditError, Rejection, Selector};
+use weight_info::WeightInfo;
+pub use quota::{CheckQuota, Quota, QuotaUsage, RemainingQuota};
+pub use relay::RelayedCall;
+pub use registry::{NameRecord, NameVersion, ResolvedName};
+pub use scheduler::ScheduledCall;
 
 type CodeHash<T> = <T as frame_system::Config>::Hash;
 
+type WrapperWeightInfo<T> = <T as Config>::WeightInfo;
+
+type ContractsWeightInfo<T> = <T as pallet_contracts::Config>::WeightInfo;
+
 type BalanceOf<T> =
 	<<T as pallet_contracts::Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
+/// A single call of a [`Pallet::batch_call`]: `(dest, value, gas_limit, data)`.
+pub type BatchedCall<T> = (
+	<<T as frame_system::Config>::Lookup as StaticLookup>::Source,
+	BalanceOf<T>,
+	Weight,
+	Vec<u8>,
+);
+
+/// A [`RelayedCall`] of the runtime.
+pub type RelayedCallOf<T> = RelayedCall<
+	<T as frame_system::Config>::AccountId,
+	<T as frame_system::Config>::BlockNumber,
+	<T as frame_system::Config>::Hash,
+>;
+
+/// What a contract execution through the wrapper produced.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct ExecutionOutcome<Hash> {
+	/// The weight consumed as reported by the contracts pallet.
+	pub gas_consumed: Weight,
+	/// The flags returned by the contract or `None` if it did not return.
+	pub flags: Option<u32>,
+	/// The hash of the data returned by the contract or `None` if it did not return.
+	pub output_hash: Option<Hash>,
+	/// The result of the dispatch.
+	pub result: DispatchResult,
+}
+
+impl<Hash> ExecutionOutcome<Hash> {
+	/// Collects the outcome from the dispatch `result` and the `output` of the contract.
+	///
+	/// Falls back to `gas_limit` if the result does not report the actual weight.
+	fn new(
+		result: &DispatchResultWithPostInfo,
+		output: Option<&ExecReturnValue>,
+		gas_limit: Weight,
+		hash: impl Fn(&[u8]) -> Hash,
+	) -> Self {
+		let (actual_weight, result) = match result {
+			Ok(post_info) => (post_info.actual_weight, Ok(())),
+			Err(err) => (err.post_info.actual_weight, Err(err.error)),
+		};
+		Self {
+			gas_consumed: actual_weight.unwrap_or(gas_limit),
+			flags: output.map(|output| output.flags.bits()),
+			output_hash: output.map(|output| hash(&output.data)),
+			result,
+		}
+	}
+}
+
 // Re-export pallet items so that they can be accessed from the crate namespace.
 pub use pallet::*;
 
@@ -30,7 +110,6 @@ pub mod pallet {
 		weights::PostDispatchInfo
 	};
     use frame_system::pallet_prelude::*;
-	use frame_system::RawOrigin;
     use sp_std::vec::Vec; // Step 3.1 will include this in `Cargo.toml`
 	use super::*;
 
@@ -39,28 +118,258 @@ pub mod pallet {
 		frame_system::Config + pallet_contracts::Config + pallet_sudo::Config {
 		// type Currency: Currency<Self::AccountId>;
 		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// Weight information for the extrinsics of this pallet.
+		type WeightInfo: WeightInfo;
+
+		/// The signature of a [`RelayedCall`], usually the `MultiSignature` of the runtime.
+		type OffchainSignature: Verify<Signer = Self::OffchainPublic> + Parameter;
+
+		/// The public key that verifies an `OffchainSignature`.
+		type OffchainPublic: IdentifyAccount<AccountId = Self::AccountId>;
+
+		/// The maximum number of calls in a single [`Pallet::batch_call`].
+		#[pallet::constant]
+		type MaxBatchedCalls: Get<u32>;
+
+		/// The weight available to scheduled calls in each block.
+		///
+		/// Scheduled calls whose gas limits do not fit are deferred to the next block.
+		#[pallet::constant]
+		type MaxScheduledWeight: Get<Weight>;
+
+		/// The maximum number of calls that can be scheduled for a single block.
+		#[pallet::constant]
+		type MaxScheduledPerBlock: Get<u32>;
+
+		/// The maximum length of a name in the contract name registry.
+		#[pallet::constant]
+		type MaxNameLength: Get<u32>;
     }
     #[pallet::event]   // <-- Step 3. code block will replace this.
+	#[pallet::metadata(
+		T::AccountId = "AccountId",
+		T::BlockNumber = "BlockNumber",
+		CodeHash<T> = "Hash",
+		ExecutionOutcome<T::Hash> = "ExecutionOutcome",
+	)]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
-	/// Event emitted when a proof has been claimed. [who, claim]
-		/// Event emitted when a claim is revoked by the owner. [who, claim]
-		SudoContractDone(DispatchResult),
+		/// A contract was called. [caller, contract, outcome]
+		ContractCalled(T::AccountId, T::AccountId, ExecutionOutcome<T::Hash>),
+		/// A contract was instantiated or failed to. [deployer, contract, code_hash, outcome]
+		ContractInstantiated(T::AccountId, T::AccountId, CodeHash<T>, ExecutionOutcome<T::Hash>),
+		/// Code was uploaded for approval. [uploader, code_hash, result]
+		CodeUploaded(T::AccountId, CodeHash<T>, DispatchResult),
+		/// The schedule was updated. [who]
+		///
+		/// `who` is `None` if the update was made by root.
+		ScheduleUpdated(Option<T::AccountId>),
+		/// All calls of a batch succeeded. [caller, calls]
+		BatchCompleted(T::AccountId, u32),
+		/// A call of a batch failed and the whole batch was rolled back. [caller, index, error]
+		BatchInterrupted(T::AccountId, u32, DispatchError),
+		/// The code of a contract was replaced. [contract, old_code_hash, new_code_hash]
+		CodeUpgraded(T::AccountId, CodeHash<T>, CodeHash<T>),
+		/// A surcharge was claimed for a contract. [who, contract, aux_sender]
+		SurchargeClaimed(Option<T::AccountId>, T::AccountId, Option<T::AccountId>),
+		/// The access control list of a contract was changed. [contract]
+		AccessControlUpdated(T::AccountId),
+		/// The quota that applies to accounts without their own quota was changed. [quota]
+		DefaultQuotaSet(Option<Quota<T::BlockNumber>>),
+		/// The quota of an account was changed. [who, quota]
+		AccountQuotaSet(T::AccountId, Option<Quota<T::BlockNumber>>),
+		/// A contract call was scheduled. [id, origin, contract, first_run]
+		CallScheduled(u64, T::AccountId, T::AccountId, T::BlockNumber),
+		/// A scheduled call was made. [id, run, outcome]
+		ScheduledCallExecuted(u64, u32, ExecutionOutcome<T::Hash>),
+		/// A scheduled call made its last run and was removed. [id]
+		ScheduledCallCompleted(u64),
+		/// A scheduled call was cancelled before its last run. [id]
+		ScheduledCallCancelled(u64),
+		/// An account was allowed to relay calls. [relayer]
+		RelayerAdded(T::AccountId),
+		/// An account is no longer allowed to relay calls. [relayer]
+		RelayerRemoved(T::AccountId),
+		/// A call signed off-chain was relayed. The outcome is reported by the following
+		/// [`Event::ContractCalled`]. [relayer, signer, nonce]
+		CallRelayed(T::AccountId, T::AccountId, u64),
+		/// A name was registered. [name, owner]
+		NameRegistered(Vec<u8>, T::AccountId),
+		/// A new version of a name was published. [name, version, contract, code_hash]
+		NameVersionPublished(Vec<u8>, u32, T::AccountId, CodeHash<T>),
+		/// A name was given to a new owner. [name, owner]
+		NameTransferred(Vec<u8>, T::AccountId),
 	}
 
     #[pallet::error]   // <-- Step 4. code block will replace this.
     pub enum Error<T> {
 		/// The origin is not the root origin
 		NotRootOrigin,
+		/// The origin is neither root nor the owner of the contract.
+		NotContractOwner,
+		/// The caller is not in the account allowlist of the contract.
+		CallerNotAllowed,
+		/// The contract only accepts listed messages and the input data has no selector.
+		MissingSelector,
+		/// The contract does not accept calls to this message.
+		SelectorNotAllowed,
+		/// The caller is not allowed to call this message of the contract.
+		CallerNotAllowedForSelector,
+		/// The edit addresses a single message on an account allowlist or vice versa.
+		AccessControlMismatch,
+		/// The permission to revoke was never granted.
+		AccessNotGranted,
+		/// The sender has used up its quota for the current period.
+		QuotaExceeded,
+		/// A quota must have a period of at least one block.
+		InvalidQuota,
+		/// The batch contains more than `MaxBatchedCalls` calls.
+		TooManyCalls,
+		/// The contract reverted the call.
+		ContractReverted,
+		/// The first run of a scheduled call must be in a future block.
+		ScheduleInPast,
+		/// A periodic call must have a non-zero period and allow at least one run.
+		InvalidSchedule,
+		/// The gas limit of a scheduled call exceeds `MaxScheduledWeight`.
+		ScheduledGasLimitTooHigh,
+		/// No more calls can be scheduled for the requested block.
+		AgendaFull,
+		/// There is no scheduled call with the given id.
+		UnknownScheduledCall,
+		/// The sender is not allowed to relay calls.
+		NotRelayer,
+		/// The relayed call was signed for another chain.
+		WrongGenesisHash,
+		/// The relayed call expired.
+		RelayedCallExpired,
+		/// The nonce of the relayed call is not the next nonce of its signer.
+		InvalidRelayNonce,
+		/// The signature does not match the relayed call and its signer.
+		InvalidRelaySignature,
+		/// The name is not of the form `namespace/name` or longer than `MaxNameLength`.
+		InvalidName,
+		/// The name is already registered.
+		NameTaken,
+		/// The name is not registered.
+		UnknownName,
+		/// The origin is neither root nor the owner of the name.
+		NotNameOwner,
+		/// There is no alive contract at the given address.
+		NotAContract,
+	}
+
+	impl<T> From<Rejection> for Error<T> {
+		fn from(rejection: Rejection) -> Self {
+			match rejection {
+				Rejection::CallerNotAllowed => Error::<T>::CallerNotAllowed,
+				Rejection::MissingSelector => Error::<T>::MissingSelector,
+				Rejection::SelectorNotAllowed => Error::<T>::SelectorNotAllowed,
+				Rejection::CallerNotAllowedForSelector => Error::<T>::CallerNotAllowedForSelector,
+			}
+		}
+	}
+
+	impl<T> From<EditError> for Error<T> {
+		fn from(error: EditError) -> Self {
+			match error {
+				EditError::Mismatch => Error::<T>::AccessControlMismatch,
+				EditError::NotGranted => Error::<T>::AccessNotGranted,
+			}
+		}
 	}
 
     #[pallet::pallet]
     #[pallet::generate_store(pub(super) trait Store)]
     pub struct Pallet<T>(_);
-    
-    // #[pallet::storage] // <-- Step 5. code block will replace this.
-    #[pallet::hooks]
-    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+	/// The account that instantiated a contract through this pallet.
+	#[pallet::storage]
+	#[pallet::getter(fn contract_owner)]
+	pub type ContractOwners<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, T::AccountId>;
+
+	/// Restricts who may call a contract through [`Pallet::call`].
+	///
+	/// Contracts without an entry accept every signed account.
+	#[pallet::storage]
+	#[pallet::getter(fn access_control)]
+	pub type AccessControlLists<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, AccessControl<T::AccountId>, ValueQuery>;
+
+	/// The quota of every account that has no entry in [`AccountQuotas`].
+	///
+	/// Without a default quota such accounts are not limited.
+	#[pallet::storage]
+	#[pallet::getter(fn default_quota)]
+	pub type DefaultQuota<T: Config> = StorageValue<_, Quota<T::BlockNumber>>;
+
+	/// Quotas that override the [`DefaultQuota`] for single accounts.
+	#[pallet::storage]
+	#[pallet::getter(fn account_quota)]
+	pub type AccountQuotas<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, Quota<T::BlockNumber>>;
+
+	/// What each account consumed of its quota in its current period.
+	#[pallet::storage]
+	pub type QuotaUsages<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, QuotaUsage<T::BlockNumber>, ValueQuery>;
+
+	/// The id assigned to the next scheduled call.
+	#[pallet::storage]
+	pub type NextScheduledCallId<T: Config> = StorageValue<_, u64, ValueQuery>;
+
+	/// The calls registered through [`Pallet::schedule_call`] by id.
+	#[pallet::storage]
+	#[pallet::getter(fn scheduled_call)]
+	pub type ScheduledCalls<T: Config> =
+		StorageMap<_, Twox64Concat, u64, ScheduledCall<T::AccountId, T::BlockNumber>>;
+
+	/// The ids of the scheduled calls due at each block in the order they are made.
+	#[pallet::storage]
+	pub type Agenda<T: Config> =
+		StorageMap<_, Twox64Concat, T::BlockNumber, Vec<u64>, ValueQuery>;
+
+	/// The accounts that may submit calls signed by other accounts.
+	#[pallet::storage]
+	#[pallet::getter(fn is_relayer)]
+	pub type Relayers<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, bool, ValueQuery>;
+
+	/// The nonce the next [`RelayedCall`] of each signer must carry.
+	#[pallet::storage]
+	#[pallet::getter(fn relay_nonce)]
+	pub type RelayNonces<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, u64, ValueQuery>;
+
+	/// The names of the contract name registry.
+	#[pallet::storage]
+	#[pallet::getter(fn name_record)]
+	pub type Names<T: Config> =
+		StorageMap<_, Blake2_128Concat, Vec<u8>, NameRecord<T::AccountId>>;
+
+	/// The version history of every registered name.
+	#[pallet::storage]
+	pub type NameVersions<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat, Vec<u8>,
+		Twox64Concat, u32,
+		NameVersion<T::AccountId, CodeHash<T>, T::BlockNumber>,
+	>;
+
+	/// The name and version last published for each contract.
+	#[pallet::storage]
+	pub type ContractNames<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, (Vec<u8>, u32)>;
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T>
+	where
+		T::AccountId: UncheckedFrom<T::Hash>,
+		T::AccountId: AsRef<[u8]>,
+	{
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			Self::service_agenda(now)
+		}
+	}
     
     #[pallet::call]   // <-- Step 6. code block will replace this.
     impl<T: Config> Pallet<T>
@@ -73,7 +382,13 @@ pub mod pallet {
 		/// * If the account is a regular account, any value will be transferred.
 		/// * If no account exists and the call value is not less than `existential_deposit`,
 		/// a regular account will be created and any value will be transferred.
-		#[pallet::weight(0)]
+		///
+		/// The call is rejected if the access control list of `dest` does not admit the caller.
+		#[pallet::weight(
+			WrapperWeightInfo::<T>::call()
+				.saturating_add(ContractsWeightInfo::<T>::call(T::MaxCodeSize::get() / 1024))
+				.saturating_add(*gas_limit)
+		)]
 		pub fn call(
 			origin: OriginFor<T>,
 			dest: <T::Lookup as StaticLookup>::Source,
@@ -81,16 +396,91 @@ pub mod pallet {
 			#[pallet::compact] gas_limit: Weight,
 			data: Vec<u8>,
 		) -> DispatchResultWithPostInfo {
-            let result = pallet_contracts::Pallet::<T>::call(
-                origin, dest, value, gas_limit, data
-            );	
-			Self::deposit_event(Event::SudoContractDone(
-				result.map(|_| ()).map_err(
-					|e| e.error
+			let caller = ensure_signed(origin)?;
+			let dest = T::Lookup::lookup(dest)?;
+			<AccessControlLists<T>>::get(&dest)
+				.check(&caller, &data)
+				.map_err(Error::<T>::from)?;
+			Self::ensure_quota_for(&caller, 1, gas_limit)?;
+			let (result, output) = pallet_contracts::Pallet::<T>::call_with_output(
+				caller.clone(), dest.clone(), value, gas_limit, data
+			);
+			Self::note_usage(&caller, 1, &result, gas_limit);
+			let actual_weight = Self::actual_weight(WrapperWeightInfo::<T>::call(), &result);
+			let outcome = Self::outcome(&result, output.as_ref(), gas_limit);
+			Self::deposit_event(Event::ContractCalled(caller, dest, outcome));
+
+			Ok(Self::feeless(actual_weight))
+		}
+
+		/// Makes several calls as one atomic operation.
+		///
+		/// The calls are made in order inside a single storage transaction. If a call fails,
+		/// traps or reverts, the effects of all calls are rolled back and
+		/// [`Event::BatchInterrupted`] reports the index of the failing call. Every call has
+		/// to be admitted by the access control list of its destination.
+		#[pallet::weight(
+			WrapperWeightInfo::<T>::batch_call(calls.len() as u32)
+				.saturating_add(
+					ContractsWeightInfo::<T>::call(T::MaxCodeSize::get() / 1024)
+						.saturating_mul(calls.len() as Weight)
 				)
-			));
+				.saturating_add(
+					calls.iter().fold(0, |weight: Weight, call| weight.saturating_add(call.2))
+				)
+		)]
+		pub fn batch_call(
+			origin: OriginFor<T>,
+			calls: Vec<BatchedCall<T>>,
+		) -> DispatchResultWithPostInfo {
+			let caller = ensure_signed(origin)?;
+			ensure!(calls.len() as u32 <= T::MaxBatchedCalls::get(), Error::<T>::TooManyCalls);
+			let calls = calls.into_iter()
+				.map(|(dest, value, gas_limit, data)| {
+					let dest = T::Lookup::lookup(dest)?;
+					<AccessControlLists<T>>::get(&dest)
+						.check(&caller, &data)
+						.map_err(Error::<T>::from)?;
+					Ok((dest, value, gas_limit, data))
+				})
+				.collect::<Result<Vec<_>, DispatchError>>()?;
+			let gas_limit = calls.iter()
+				.fold(0, |weight: Weight, call| weight.saturating_add(call.2));
+			let count = calls.len() as u32;
+			// Every batched call counts against the quota, an empty batch as one call.
+			Self::ensure_quota_for(&caller, count.max(1), gas_limit)?;
 
-			Ok(Pays::No.into())
+			let mut gas_consumed: Weight = 0;
+			let result = with_transaction(|| {
+				for (index, (dest, value, gas_limit, data)) in calls.into_iter().enumerate() {
+					let (result, output) = pallet_contracts::Pallet::<T>::call_with_output(
+						caller.clone(), dest.clone(), value, gas_limit, data
+					);
+					let outcome = Self::outcome(&result, output.as_ref(), gas_limit);
+					gas_consumed = gas_consumed.saturating_add(outcome.gas_consumed);
+					let result = outcome.result.and_then(|_| {
+						match output {
+							Some(output) if output.flags.contains(ReturnFlags::REVERT) =>
+								Err(Error::<T>::ContractReverted.into()),
+							_ => Ok(()),
+						}
+					});
+					if let Err(error) = result {
+						return TransactionOutcome::Rollback(Err((index as u32, error)));
+					}
+					Self::deposit_event(Event::ContractCalled(caller.clone(), dest, outcome));
+				}
+				TransactionOutcome::Commit(Ok(()))
+			});
+
+			Self::note_usage(&caller, count.max(1), &Ok(Some(gas_consumed).into()), gas_consumed);
+			match result {
+				Ok(()) => Self::deposit_event(Event::BatchCompleted(caller, count)),
+				Err((index, error)) =>
+					Self::deposit_event(Event::BatchInterrupted(caller, index, error)),
+			}
+			let actual_weight = WrapperWeightInfo::<T>::batch_call(count).saturating_add(gas_consumed);
+			Ok(Self::feeless(Some(actual_weight)))
 		}
 
 		/// Instantiates a new contract from the supplied `code` optionally transferring
@@ -105,6 +495,8 @@ pub mod pallet {
 		/// * `code`: The contract code to deploy in raw bytes.
 		/// * `data`: The input data to pass to the contract constructor.
 		/// * `salt`: Used for the address derivation. See [`Pallet::contract_address`].
+		/// * `metadata`: The (usually compressed) ink! metadata of the code. It is attached to
+		///   the code hash if the instantiation succeeds and the code has no metadata yet.
 		///
 		/// Instantiation is executed as follows:
 		///
@@ -115,7 +507,16 @@ pub mod pallet {
 		/// - The smart-contract account is created at the computed address.
 		/// - The `endowment` is transferred to the new account.
 		/// - The `deploy` function is executed in the context of the newly-created account.
-		#[pallet::weight(0)]
+		#[pallet::weight(
+			WrapperWeightInfo::<T>::instantiate_with_code(
+				(code.len() + metadata.as_ref().map_or(0, Vec::len)) as u32 / 1024
+			)
+				.saturating_add(ContractsWeightInfo::<T>::instantiate_with_code(
+					code.len() as u32 / 1024,
+					salt.len() as u32 / 1024,
+				))
+				.saturating_add(*gas_limit)
+		)]
 		pub fn instantiate_with_code(
 			origin: OriginFor<T>,
 			#[pallet::compact] endowment: BalanceOf<T>,
@@ -123,18 +524,19 @@ pub mod pallet {
 			code: Vec<u8>,
 			data: Vec<u8>,
 			salt: Vec<u8>,
+			metadata: Option<Vec<u8>>,
 		) -> DispatchResultWithPostInfo {
 			Self::is_root(origin.clone())?;
-            let result = pallet_contracts::Pallet::<T>::
-				instantiate_with_code(
-                	origin, endowment, gas_limit, code, data, salt
-            );
-			Self::deposit_event(Event::SudoContractDone(
-				result.map(|_| ()).map_err(
-					|e| e.error
-				)
-			));
-			Ok(Pays::No.into())
+			let deployer = ensure_signed(origin)?;
+			Self::ensure_metadata_fits(&metadata)?;
+			let code_hash = T::Hashing::hash(&code);
+			let weight = WrapperWeightInfo::<T>::instantiate_with_code(
+				(code.len() + metadata.as_ref().map_or(0, Vec::len)) as u32 / 1024
+			);
+			Self::do_instantiate(
+				deployer, endowment, gas_limit, code_hash, Code::Upload(Bytes(code)), data, salt,
+				metadata, weight,
+			)
 		}
 		/// Updates the schedule for metering contracts.
 		///
@@ -142,13 +544,19 @@ pub mod pallet {
 		/// If a schedule does not change the instruction weights the version does not
 		/// need to be increased. Therefore we allow storing a schedule that has the same
 		/// version as the stored one.
-		#[pallet::weight(0)]
+		#[pallet::weight(
+			WrapperWeightInfo::<T>::update_schedule()
+				.saturating_add(ContractsWeightInfo::<T>::update_schedule())
+		)]
 		pub fn update_schedule(
 			origin: OriginFor<T>,
 			schedule: Schedule<T>
 		) -> DispatchResultWithPostInfo {
 			Self::is_root(origin.clone())?;
-			pallet_contracts::Pallet::update_schedule(origin, schedule)
+			let who = ensure_signed(origin.clone()).ok();
+			let post_info = pallet_contracts::Pallet::update_schedule(origin, schedule)?;
+			Self::deposit_event(Event::ScheduleUpdated(who));
+			Ok(post_info)
 		}
 
 		/// Instantiates a contract from a previously deployed wasm binary.
@@ -156,7 +564,14 @@ pub mod pallet {
 		/// This function is identical to [`Self::instantiate_with_code`] but without the
 		/// code deployment step. Instead, the `code_hash` of an on-chain deployed wasm binary
 		/// must be supplied.
-		#[pallet::weight(0)]
+		#[pallet::weight(
+			WrapperWeightInfo::<T>::instantiate()
+				.saturating_add(ContractsWeightInfo::<T>::instantiate(
+					T::MaxCodeSize::get() / 1024,
+					salt.len() as u32 / 1024,
+				))
+				.saturating_add(*gas_limit)
+		)]
 		pub fn instantiate(
 			origin: OriginFor<T>,
 			#[pallet::compact] endowment: BalanceOf<T>,
@@ -166,15 +581,11 @@ pub mod pallet {
 			salt: Vec<u8>,
 		) -> DispatchResultWithPostInfo {
 			Self::is_root(origin.clone())?;
-			let result = pallet_contracts::Pallet::<T>::instantiate(
-				origin, endowment, gas_limit, code_hash, data, salt
-			);
-			Self::deposit_event(Event::SudoContractDone(
-				result.map(|_| ()).map_err(
-					|e| e.error
-				)
-			));
-			Ok(Pays::No.into())
+			let deployer = ensure_signed(origin)?;
+			Self::do_instantiate(
+				deployer, endowment, gas_limit, code_hash, Code::Existing(code_hash), data, salt,
+				None, WrapperWeightInfo::<T>::instantiate(),
+			)
 		}
 		/// Allows block producers to claim a small reward for evicting a contract. If a block
 		/// producer fails to do so, a regular users will be allowed to claim the reward.
@@ -185,16 +596,374 @@ pub mod pallet {
 		///
 		/// If contract is not evicted as a result of this call, [`Error::ContractNotEvictable`]
 		/// is returned and the sender is not eligible for the reward.
-		#[pallet::weight(0)]
+		#[pallet::weight(
+			WrapperWeightInfo::<T>::claim_surcharge()
+				.saturating_add(ContractsWeightInfo::<T>::claim_surcharge(T::MaxCodeSize::get() / 1024))
+		)]
 		pub fn claim_surcharge(
 			origin: OriginFor<T>,
 			dest: T::AccountId,
 			aux_sender: Option<T::AccountId>
 		) -> DispatchResultWithPostInfo {
-			Self::is_root(origin.clone())?;
-			pallet_contracts::Pallet::<T>::claim_surcharge(
-				origin, dest, aux_sender
+			Self::ensure_sudo(origin.clone())?;
+			let who = ensure_signed(origin.clone()).ok();
+			let post_info = pallet_contracts::Pallet::<T>::claim_surcharge(
+				origin, dest.clone(), aux_sender.clone()
+			)?;
+			Self::deposit_event(Event::SurchargeClaimed(who, dest, aux_sender));
+			Ok(post_info)
+		}
+
+		/// Stores the supplied `code` without instantiating it.
+		///
+		/// The code has to be approved through `pallet_contracts::Pallet::approve_code`
+		/// before [`Self::instantiate`] accepts its hash. The (usually compressed) ink!
+		/// `metadata` of the code is attached to its hash if the upload succeeds.
+		///
+		/// The origin must be an authorized deployer. Failed uploads are paid for.
+		#[pallet::weight(
+			WrapperWeightInfo::<T>::upload_code(
+				(code.len() + metadata.as_ref().map_or(0, Vec::len)) as u32 / 1024
 			)
+				.saturating_add(ContractsWeightInfo::<T>::instrument(code.len() as u32 / 1024))
+				.saturating_add(T::DbWeight::get().reads_writes(1, 3))
+		)]
+		pub fn upload_code(
+			origin: OriginFor<T>,
+			code: Vec<u8>,
+			metadata: Option<Vec<u8>>,
+		) -> DispatchResultWithPostInfo {
+			Self::is_root(origin.clone())?;
+			let uploader = ensure_signed(origin.clone())?;
+			Self::ensure_quota_for(&uploader, 1, 0)?;
+			Self::ensure_metadata_fits(&metadata)?;
+			let code_hash = T::Hashing::hash(&code);
+			let result = pallet_contracts::Pallet::<T>::upload_code(origin, code);
+			Self::note_usage(&uploader, 1, &result, 0);
+			if let (Ok(_), Some(metadata)) = (&result, metadata) {
+				Self::attach_metadata(code_hash, metadata);
+			}
+			Self::deposit_event(Event::CodeUploaded(
+				uploader, code_hash, result.as_ref().map(|_| ()).map_err(|e| e.error),
+			));
+			result?;
+			Ok(Pays::No.into())
+		}
+
+		/// Replaces the code of `contract` with the stored and approved `code_hash`.
+		///
+		/// The storage of the contract is kept, so the new code has to be compatible with its
+		/// layout. The origin must be root or the owner of the contract.
+		#[pallet::weight(WrapperWeightInfo::<T>::set_code())]
+		pub fn set_code(
+			origin: OriginFor<T>,
+			contract: T::AccountId,
+			code_hash: CodeHash<T>,
+		) -> DispatchResultWithPostInfo {
+			Self::ensure_owner(origin, &contract)?;
+			let old_code_hash = pallet_contracts::Pallet::<T>::set_contract_code(&contract, code_hash)?;
+			Self::deposit_event(Event::CodeUpgraded(contract, old_code_hash, code_hash));
+			Ok(Pays::No.into())
+		}
+
+		/// Sets the quota of every account that has no quota of its own.
+		///
+		/// Passing `None` lifts the limit for those accounts.
+		#[pallet::weight(WrapperWeightInfo::<T>::set_default_quota())]
+		pub fn set_default_quota(
+			origin: OriginFor<T>,
+			quota: Option<Quota<T::BlockNumber>>,
+		) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			Self::ensure_valid_quota(&quota)?;
+			<DefaultQuota<T>>::set(quota);
+			Self::deposit_event(Event::DefaultQuotaSet(quota));
+			Ok(Pays::No.into())
+		}
+
+		/// Sets the quota of `who`, overriding the default quota.
+		///
+		/// Passing `None` makes the default quota apply to `who` again.
+		#[pallet::weight(WrapperWeightInfo::<T>::set_account_quota())]
+		pub fn set_account_quota(
+			origin: OriginFor<T>,
+			who: T::AccountId,
+			quota: Option<Quota<T::BlockNumber>>,
+		) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			Self::ensure_valid_quota(&quota)?;
+			<AccountQuotas<T>>::set(&who, quota);
+			Self::deposit_event(Event::AccountQuotaSet(who, quota));
+			Ok(Pays::No.into())
+		}
+
+		/// Replaces the access control list of `contract`.
+		///
+		/// Setting [`AccessControl::AllowAll`] removes the list from storage.
+		/// The origin must be root or the owner of the contract.
+		#[pallet::weight(WrapperWeightInfo::<T>::set_access_control(acl.accounts_len()))]
+		pub fn set_access_control(
+			origin: OriginFor<T>,
+			contract: T::AccountId,
+			acl: AccessControl<T::AccountId>,
+		) -> DispatchResultWithPostInfo {
+			Self::ensure_owner(origin, &contract)?;
+			if acl == AccessControl::AllowAll {
+				<AccessControlLists<T>>::remove(&contract);
+			} else {
+				<AccessControlLists<T>>::insert(&contract, acl);
+			}
+			Self::deposit_event(Event::AccessControlUpdated(contract));
+			Ok(Pays::No.into())
+		}
+
+		/// Allows `who` to call `contract` or, if `selector` is supplied, only that message.
+		///
+		/// The origin must be root or the owner of the contract.
+		#[pallet::weight(WrapperWeightInfo::<T>::grant_access())]
+		pub fn grant_access(
+			origin: OriginFor<T>,
+			contract: T::AccountId,
+			who: T::AccountId,
+			selector: Option<Selector>,
+		) -> DispatchResultWithPostInfo {
+			Self::ensure_owner(origin, &contract)?;
+			<AccessControlLists<T>>::try_mutate(&contract, |acl| acl.grant(who, selector))
+				.map_err(Error::<T>::from)?;
+			Self::deposit_event(Event::AccessControlUpdated(contract));
+			Ok(Pays::No.into())
+		}
+
+		/// Revokes a permission given by [`Self::grant_access`].
+		///
+		/// The origin must be root or the owner of the contract.
+		#[pallet::weight(WrapperWeightInfo::<T>::revoke_access())]
+		pub fn revoke_access(
+			origin: OriginFor<T>,
+			contract: T::AccountId,
+			who: T::AccountId,
+			selector: Option<Selector>,
+		) -> DispatchResultWithPostInfo {
+			Self::ensure_owner(origin, &contract)?;
+			<AccessControlLists<T>>::try_mutate(&contract, |acl| acl.revoke(&who, selector))
+				.map_err(Error::<T>::from)?;
+			Self::deposit_event(Event::AccessControlUpdated(contract));
+			Ok(Pays::No.into())
+		}
+
+		/// Schedules a call of `dest` on behalf of `call_origin` at block `start`.
+		///
+		/// If a `period` is given the call is repeated every `period` blocks until it made
+		/// `max_runs` runs or, without `max_runs`, until it is cancelled. The calls are made
+		/// in `on_initialize` and their outcome is reported by
+		/// [`Event::ScheduledCallExecuted`]. Only the root origin can schedule calls.
+		#[pallet::weight(WrapperWeightInfo::<T>::schedule_call())]
+		pub fn schedule_call(
+			origin: OriginFor<T>,
+			call_origin: T::AccountId,
+			dest: <T::Lookup as StaticLookup>::Source,
+			#[pallet::compact] gas_limit: Weight,
+			data: Vec<u8>,
+			start: T::BlockNumber,
+			period: Option<T::BlockNumber>,
+			max_runs: Option<u32>,
+		) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			let dest = T::Lookup::lookup(dest)?;
+			ensure!(
+				start > <frame_system::Pallet<T>>::block_number(),
+				Error::<T>::ScheduleInPast,
+			);
+			ensure!(
+				period.map_or(true, |period| !period.is_zero()) && max_runs != Some(0),
+				Error::<T>::InvalidSchedule,
+			);
+			ensure!(
+				gas_limit <= T::MaxScheduledWeight::get(),
+				Error::<T>::ScheduledGasLimitTooHigh,
+			);
+			let id = <NextScheduledCallId<T>>::get();
+			<Agenda<T>>::try_mutate(start, |agenda| {
+				ensure!(
+					(agenda.len() as u32) < T::MaxScheduledPerBlock::get(),
+					Error::<T>::AgendaFull,
+				);
+				agenda.push(id);
+				Ok::<_, Error<T>>(())
+			})?;
+			<NextScheduledCallId<T>>::put(id.saturating_add(1));
+			<ScheduledCalls<T>>::insert(id, ScheduledCall {
+				origin: call_origin.clone(),
+				dest: dest.clone(),
+				data,
+				gas_limit,
+				next_run: start,
+				period,
+				max_runs,
+				runs: 0,
+			});
+			Self::deposit_event(Event::CallScheduled(id, call_origin, dest, start));
+			Ok(Pays::No.into())
+		}
+
+		/// Removes the scheduled call `id` before its next run.
+		///
+		/// Only the root origin can cancel scheduled calls.
+		#[pallet::weight(WrapperWeightInfo::<T>::cancel_scheduled_call())]
+		pub fn cancel_scheduled_call(
+			origin: OriginFor<T>,
+			id: u64,
+		) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			let scheduled = <ScheduledCalls<T>>::take(id)
+				.ok_or(Error::<T>::UnknownScheduledCall)?;
+			<Agenda<T>>::mutate(scheduled.next_run, |agenda| agenda.retain(|due| *due != id));
+			Self::deposit_event(Event::ScheduledCallCancelled(id));
+			Ok(Pays::No.into())
+		}
+
+		/// Allows `relayer` to submit calls through [`Self::relay_call`].
+		///
+		/// Only the root origin can add relayers.
+		#[pallet::weight(WrapperWeightInfo::<T>::add_relayer())]
+		pub fn add_relayer(
+			origin: OriginFor<T>,
+			relayer: T::AccountId,
+		) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			<Relayers<T>>::insert(&relayer, true);
+			Self::deposit_event(Event::RelayerAdded(relayer));
+			Ok(Pays::No.into())
+		}
+
+		/// Revokes the permission given by [`Self::add_relayer`].
+		///
+		/// Only the root origin can remove relayers.
+		#[pallet::weight(WrapperWeightInfo::<T>::remove_relayer())]
+		pub fn remove_relayer(
+			origin: OriginFor<T>,
+			relayer: T::AccountId,
+		) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			ensure!(Self::is_relayer(&relayer), Error::<T>::NotRelayer);
+			<Relayers<T>>::remove(&relayer);
+			Self::deposit_event(Event::RelayerRemoved(relayer));
+			Ok(Pays::No.into())
+		}
+
+		/// Calls a contract on behalf of `signer`, who signed `call` off-chain.
+		///
+		/// The origin must be a relayer added by [`Self::add_relayer`]. `signature` must be the
+		/// signature of `signer` over the SCALE encoded `call`. The contract sees `signer` as
+		/// its caller, so the access control list of the contract and the quota of `signer`
+		/// apply. The nonce of `signer` is used up even if the contract call fails.
+		#[pallet::weight(
+			WrapperWeightInfo::<T>::relay_call(call.data.len() as u32 / 1024)
+				.saturating_add(ContractsWeightInfo::<T>::call(T::MaxCodeSize::get() / 1024))
+				.saturating_add(call.gas_limit)
+		)]
+		pub fn relay_call(
+			origin: OriginFor<T>,
+			signer: T::AccountId,
+			call: RelayedCallOf<T>,
+			signature: T::OffchainSignature,
+		) -> DispatchResultWithPostInfo {
+			let relayer = ensure_signed(origin)?;
+			ensure!(Self::is_relayer(&relayer), Error::<T>::NotRelayer);
+			ensure!(
+				call.genesis_hash == <frame_system::Pallet<T>>::block_hash(T::BlockNumber::zero()),
+				Error::<T>::WrongGenesisHash,
+			);
+			ensure!(
+				<frame_system::Pallet<T>>::block_number() <= call.expiry,
+				Error::<T>::RelayedCallExpired,
+			);
+			ensure!(call.nonce == Self::relay_nonce(&signer), Error::<T>::InvalidRelayNonce);
+			ensure!(
+				signature.verify(&call.encode()[..], &signer),
+				Error::<T>::InvalidRelaySignature,
+			);
+			<AccessControlLists<T>>::get(&call.dest)
+				.check(&signer, &call.data)
+				.map_err(Error::<T>::from)?;
+			Self::ensure_quota_for(&signer, 1, call.gas_limit)?;
+			<RelayNonces<T>>::insert(&signer, call.nonce.saturating_add(1));
+
+			let weight = WrapperWeightInfo::<T>::relay_call(call.data.len() as u32 / 1024);
+			let RelayedCall { dest, data, gas_limit, nonce, .. } = call;
+			let (result, output) = pallet_contracts::Pallet::<T>::call_with_output(
+				signer.clone(), dest.clone(), Zero::zero(), gas_limit, data
+			);
+			Self::note_usage(&signer, 1, &result, gas_limit);
+			let actual_weight = Self::actual_weight(weight, &result);
+			let outcome = Self::outcome(&result, output.as_ref(), gas_limit);
+			Self::deposit_event(Event::CallRelayed(relayer, signer.clone(), nonce));
+			Self::deposit_event(Event::ContractCalled(signer, dest, outcome));
+			Ok(Self::feeless(actual_weight))
+		}
+
+		/// Registers `name` for `owner` with `contract` as its first version.
+		///
+		/// `name` must be of the form `namespace/name`, see [`registry`]. Only the root origin
+		/// can register names.
+		#[pallet::weight(WrapperWeightInfo::<T>::register_name())]
+		pub fn register_name(
+			origin: OriginFor<T>,
+			name: Vec<u8>,
+			owner: T::AccountId,
+			contract: T::AccountId,
+		) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			ensure!(
+				name.len() <= T::MaxNameLength::get() as usize && registry::is_valid_name(&name),
+				Error::<T>::InvalidName,
+			);
+			ensure!(!<Names<T>>::contains_key(&name), Error::<T>::NameTaken);
+			let code_hash = pallet_contracts::Pallet::<T>::code_hash(&contract)
+				.ok_or(Error::<T>::NotAContract)?;
+
+			let mut record = NameRecord { owner: owner.clone(), latest: 0 };
+			let version = Self::publish_version(&name, &mut record, contract.clone(), code_hash);
+			<Names<T>>::insert(&name, record);
+			Self::deposit_event(Event::NameRegistered(name.clone(), owner));
+			Self::deposit_event(Event::NameVersionPublished(name, version, contract, code_hash));
+			Ok(Pays::No.into())
+		}
+
+		/// Publishes `contract` as the next version of `name`, making it the one `name`
+		/// resolves to. Earlier versions stay resolvable by their number.
+		///
+		/// The origin must be root or the owner of the name.
+		#[pallet::weight(WrapperWeightInfo::<T>::publish_name_version())]
+		pub fn publish_name_version(
+			origin: OriginFor<T>,
+			name: Vec<u8>,
+			contract: T::AccountId,
+		) -> DispatchResultWithPostInfo {
+			let mut record = Self::ensure_name_owner(origin, &name)?;
+			let code_hash = pallet_contracts::Pallet::<T>::code_hash(&contract)
+				.ok_or(Error::<T>::NotAContract)?;
+
+			let version = Self::publish_version(&name, &mut record, contract.clone(), code_hash);
+			<Names<T>>::insert(&name, record);
+			Self::deposit_event(Event::NameVersionPublished(name, version, contract, code_hash));
+			Ok(Pays::No.into())
+		}
+
+		/// Gives `name` to `new_owner`.
+		///
+		/// The origin must be root or the owner of the name.
+		#[pallet::weight(WrapperWeightInfo::<T>::transfer_name())]
+		pub fn transfer_name(
+			origin: OriginFor<T>,
+			name: Vec<u8>,
+			new_owner: T::AccountId,
+		) -> DispatchResultWithPostInfo {
+			let mut record = Self::ensure_name_owner(origin, &name)?;
+			record.owner = new_owner.clone();
+			<Names<T>>::insert(&name, record);
+			Self::deposit_event(Event::NameTransferred(name, new_owner));
+			Ok(Pays::No.into())
 		}
 	}
 
@@ -203,20 +972,256 @@ pub mod pallet {
 		T::AccountId: UncheckedFrom<T::Hash>,
 		T::AccountId: AsRef<[u8]>,
 	{
-		fn is_root(origin: OriginFor<T>) -> Result<(), DispatchError>{
-			let sudo_key = pallet_sudo::Pallet::<T>::key();
-			let test_origin = match origin.into() {
-				Ok(RawOrigin::Signed(account_id)) => {
-					let mut result = Ok(());
-					if sudo_key != account_id{
-						result = Err(DispatchError::BadOrigin);
-					}
-					result
+		/// Delegates to [`pallet_contracts::Pallet::ensure_deployer`] so that the wrapper and
+		/// the contracts pallet share the same deployer allowlist.
+		fn is_root(origin: OriginFor<T>) -> Result<(), DispatchError> {
+			pallet_contracts::Pallet::<T>::ensure_deployer(origin)
+		}
+
+		/// Admits only the root origin and the sudo key.
+		fn ensure_sudo(origin: OriginFor<T>) -> Result<(), DispatchError> {
+			match origin.into() {
+				Ok(frame_system::RawOrigin::Root) => Ok(()),
+				Ok(frame_system::RawOrigin::Signed(who)) => {
+					ensure!(who == pallet_sudo::Pallet::<T>::key(), DispatchError::BadOrigin);
+					Ok(())
+				}
+				_ => Err(DispatchError::BadOrigin),
+			}
+		}
+
+		/// Admits the root origin, the sudo key and the account that instantiated `contract`.
+		fn ensure_owner(origin: OriginFor<T>, contract: &T::AccountId) -> Result<(), DispatchError> {
+			match origin.into() {
+				Ok(frame_system::RawOrigin::Root) => Ok(()),
+				Ok(frame_system::RawOrigin::Signed(who)) => {
+					ensure!(
+						who == pallet_sudo::Pallet::<T>::key() ||
+							Self::contract_owner(contract).as_ref() == Some(&who),
+						Error::<T>::NotContractOwner,
+					);
+					Ok(())
 				}
-				Ok(RawOrigin::Root) => Ok(()),
 				_ => Err(DispatchError::BadOrigin),
+			}
+		}
+
+		/// Ensures that `origin` is root, the sudo key or the owner of `name` and returns the
+		/// record of the name.
+		fn ensure_name_owner(
+			origin: OriginFor<T>,
+			name: &[u8],
+		) -> Result<NameRecord<T::AccountId>, DispatchError> {
+			let record = <Names<T>>::get(name).ok_or(Error::<T>::UnknownName)?;
+			match origin.into() {
+				Ok(frame_system::RawOrigin::Root) => Ok(record),
+				Ok(frame_system::RawOrigin::Signed(who)) => {
+					ensure!(
+						who == pallet_sudo::Pallet::<T>::key() || who == record.owner,
+						Error::<T>::NotNameOwner,
+					);
+					Ok(record)
+				}
+				_ => Err(DispatchError::BadOrigin),
+			}
+		}
+
+		/// Dry-runs a call of `dest` like [`Self::call`] would make it, for the `ContractsApi`
+		/// of the runtime.
+		///
+		/// The call is rejected without executing the contract if the access control list of
+		/// `dest` does not admit `origin`. The quota of `origin` is neither checked nor used.
+		/// Messages of `seal_debug_message` are collected into the `debug_message` of the result.
+		pub fn bare_call(
+			origin: T::AccountId,
+			dest: T::AccountId,
+			value: BalanceOf<T>,
+			gas_limit: Weight,
+			data: Vec<u8>,
+		) -> ContractExecResult {
+			if let Err(rejection) = <AccessControlLists<T>>::get(&dest).check(&origin, &data) {
+				return ContractExecResult {
+					result: Err(Error::<T>::from(rejection).into()),
+					gas_consumed: 0,
+					debug_message: Bytes(Vec::new()),
+				};
+			}
+			pallet_contracts::Pallet::<T>::bare_call(origin, dest, value, gas_limit, data, true)
+		}
+
+		/// Dry-runs a call like [`Self::bare_call`] and records the call tree of the execution.
+		///
+		/// No frames are recorded if the call is rejected by the access control list.
+		pub fn bare_call_traced(
+			origin: T::AccountId,
+			dest: T::AccountId,
+			value: BalanceOf<T>,
+			gas_limit: Weight,
+			data: Vec<u8>,
+		) -> (ContractExecResult, Vec<CallFrameOf<T>>) {
+			if let Err(rejection) = <AccessControlLists<T>>::get(&dest).check(&origin, &data) {
+				let result = ContractExecResult {
+					result: Err(Error::<T>::from(rejection).into()),
+					gas_consumed: 0,
+					debug_message: Bytes(Vec::new()),
+				};
+				return (result, Vec::new());
+			}
+			pallet_contracts::Pallet::<T>::bare_call_traced(origin, dest, value, gas_limit, data)
+		}
+
+		/// Checks that `who` can make another `calls` calls consuming `weight` in total.
+		pub fn ensure_quota_for(
+			who: &T::AccountId,
+			calls: u32,
+			weight: Weight,
+		) -> Result<(), Error<T>> {
+			if let Some(quota) = Self::quota_of(who) {
+				let usage = Self::current_usage(who, &quota);
+				ensure!(
+					usage.calls.saturating_add(calls) <= quota.max_calls &&
+						usage.weight.saturating_add(weight) <= quota.max_weight,
+					Error::<T>::QuotaExceeded,
+				);
+			}
+			Ok(())
+		}
+
+		/// Returns what is left of the quota of `who` or `None` if `who` is not limited.
+		pub fn remaining_quota(who: &T::AccountId) -> Option<RemainingQuota<T::BlockNumber>> {
+			Self::quota_of(who).map(|quota| {
+				let usage = Self::current_usage(who, &quota);
+				RemainingQuota {
+					weight: quota.max_weight.saturating_sub(usage.weight),
+					calls: quota.max_calls.saturating_sub(usage.calls),
+					resets_at: usage.period_start.saturating_add(quota.period),
+				}
+			})
+		}
+
+		fn quota_of(who: &T::AccountId) -> Option<Quota<T::BlockNumber>> {
+			Self::account_quota(who).or_else(Self::default_quota)
+		}
+
+		/// The usage of `who` with a new period started if the last one is over.
+		fn current_usage(
+			who: &T::AccountId,
+			quota: &Quota<T::BlockNumber>,
+		) -> QuotaUsage<T::BlockNumber> {
+			let now = <frame_system::Pallet<T>>::block_number();
+			let usage = <QuotaUsages<T>>::get(who);
+			if now >= usage.period_start.saturating_add(quota.period) {
+				QuotaUsage { period_start: now, weight: 0, calls: 0 }
+			} else {
+				usage
+			}
+		}
+
+		/// Records `calls` calls and the weight they used against the quota of `who`.
+		///
+		/// Falls back to `weight_limit` when the call does not report its actual weight.
+		fn note_usage(
+			who: &T::AccountId,
+			calls: u32,
+			result: &DispatchResultWithPostInfo,
+			weight_limit: Weight,
+		) {
+			let quota = match Self::quota_of(who) {
+				Some(quota) => quota,
+				None => return,
 			};
-			test_origin
-		}	
+			let actual_weight = match result {
+				Ok(post_info) => post_info.actual_weight,
+				Err(err) => err.post_info.actual_weight,
+			}.unwrap_or(weight_limit);
+			let mut usage = Self::current_usage(who, &quota);
+			usage.calls = usage.calls.saturating_add(calls);
+			usage.weight = usage.weight.saturating_add(actual_weight);
+			<QuotaUsages<T>>::insert(who, usage);
+		}
+
+		fn ensure_valid_quota(quota: &Option<Quota<T::BlockNumber>>) -> Result<(), Error<T>> {
+			ensure!(
+				quota.map_or(true, |quota| !quota.period.is_zero()),
+				Error::<T>::InvalidQuota,
+			);
+			Ok(())
+		}
+
+		/// Instantiates `code` on behalf of an authorized `deployer`, who becomes the owner.
+		///
+		/// `weight` is the weight of the work done by the wrapper itself.
+		fn do_instantiate(
+			deployer: T::AccountId,
+			endowment: BalanceOf<T>,
+			gas_limit: Weight,
+			code_hash: CodeHash<T>,
+			code: Code<CodeHash<T>>,
+			data: Vec<u8>,
+			salt: Vec<u8>,
+			metadata: Option<Vec<u8>>,
+			weight: Weight,
+		) -> DispatchResultWithPostInfo {
+			Self::ensure_quota_for(&deployer, 1, gas_limit)?;
+			let contract = pallet_contracts::Pallet::<T>::contract_address(&deployer, &code_hash, &salt);
+			let (result, output) = pallet_contracts::Pallet::<T>::instantiate_with_output(
+				deployer.clone(), endowment, gas_limit, code, data, salt
+			);
+			Self::note_usage(&deployer, 1, &result, gas_limit);
+			if result.is_ok() {
+				<ContractOwners<T>>::insert(&contract, &deployer);
+				if let Some(metadata) = metadata {
+					Self::attach_metadata(code_hash, metadata);
+				}
+			}
+			let actual_weight = Self::actual_weight(weight, &result);
+			let outcome = Self::outcome(&result, output.as_ref().map(|(_, output)| output), gas_limit);
+			Self::deposit_event(Event::ContractInstantiated(deployer, contract, code_hash, outcome));
+			Ok(Self::feeless(actual_weight))
+		}
+
+		/// Fails with `MetadataTooLarge` before anything is stored if `metadata` could not be
+		/// attached.
+		fn ensure_metadata_fits(metadata: &Option<Vec<u8>>) -> Result<(), DispatchError> {
+			ensure!(
+				metadata.as_ref().map_or(true, |metadata|
+					metadata.len() as u32 <= T::MaxMetadataLen::get()
+				),
+				pallet_contracts::Error::<T>::MetadataTooLarge,
+			);
+			Ok(())
+		}
+
+		/// Attaches `metadata` to the just stored code `code_hash`.
+		fn attach_metadata(code_hash: CodeHash<T>, metadata: Vec<u8>) {
+			// The size was checked by `ensure_metadata_fits`. Metadata the code already has is
+			// kept and nothing is attached to code that was removed again.
+			let _ = pallet_contracts::Pallet::<T>::attach_metadata(code_hash, metadata);
+		}
+
+		/// The actual weight of an extrinsic whose own work weighs `weight` and whose call into
+		/// the contracts pallet returned `result`.
+		///
+		/// Returns `None`, which keeps the declared weight, if `result` does not report the
+		/// weight it actually used.
+		fn actual_weight(weight: Weight, result: &DispatchResultWithPostInfo) -> Option<Weight> {
+			match result {
+				Ok(post_info) => post_info.actual_weight,
+				Err(err) => err.post_info.actual_weight,
+			}.map(|inner_weight| weight.saturating_add(inner_weight))
+		}
+
+		/// Post dispatch information that refunds down to `actual_weight` without charging fees.
+		fn feeless(actual_weight: Option<Weight>) -> PostDispatchInfo {
+			PostDispatchInfo { actual_weight, pays_fee: Pays::No }
+		}
+
+		pub(crate) fn outcome(
+			result: &DispatchResultWithPostInfo,
+			output: Option<&ExecReturnValue>,
+			gas_limit: Weight,
+		) -> ExecutionOutcome<T::Hash> {
+			ExecutionOutcome::new(result, output, gas_limit, |data| T::Hashing::hash(data))
+		}
 	}
 }
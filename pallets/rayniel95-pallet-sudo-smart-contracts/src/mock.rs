@@ -0,0 +1,196 @@
+use crate as pallet_sudo_contracts;
+use frame_support::{
+	parameter_types,
+	traits::GenesisBuild,
+	weights::{Weight, constants::WEIGHT_PER_SECOND},
+};
+use sp_core::H256;
+use sp_runtime::{
+	traits::{BlakeTwo256, Hash, IdentityLookup}, testing::Header, AccountId32, MultiSignature,
+	MultiSigner,
+};
+use frame_system as system;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		Timestamp: pallet_timestamp::{Pallet, Call, Storage, Inherent},
+		Randomness: pallet_randomness_collective_flip::{Pallet, Call, Storage},
+		Sudo: pallet_sudo::{Pallet, Call, Config<T>, Storage, Event<T>},
+		Contracts: pallet_contracts::{Pallet, Call, Config<T>, Storage, Event<T>},
+		SudoContracts: pallet_sudo_contracts::{Pallet, Call, Storage, Event<T>},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub BlockWeights: frame_system::limits::BlockWeights =
+		frame_system::limits::BlockWeights::simple_max(2 * WEIGHT_PER_SECOND);
+	pub const SS58Prefix: u8 = 42;
+}
+
+impl system::Config for Test {
+	type BaseCallFilter = ();
+	type BlockWeights = BlockWeights;
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId32;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+	type OnSetCode = ();
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type Balance = u64;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const MinimumPeriod: u64 = 1;
+}
+
+impl pallet_timestamp::Config for Test {
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type MinimumPeriod = MinimumPeriod;
+	type WeightInfo = ();
+}
+
+impl pallet_randomness_collective_flip::Config for Test {}
+
+impl pallet_sudo::Config for Test {
+	type Event = Event;
+	type Call = Call;
+}
+
+// Deposits and rent are disabled like in the node runtime.
+parameter_types! {
+	pub const SignedClaimHandicap: u64 = 2;
+	pub const TombstoneDeposit: u64 = 0;
+	pub const DepositPerContract: u64 = 0;
+	pub const DepositPerStorageByte: u64 = 0;
+	pub const DepositPerStorageItem: u64 = 0;
+	pub const SurchargeReward: u64 = 0;
+	pub const RentFree: bool = true;
+	pub const MaxDepth: u32 = 32;
+	pub const MaxValueSize: u32 = 16 * 1024;
+	pub const DeletionQueueDepth: u32 = 1024;
+	pub const DeletionWeightLimit: Weight = 500_000_000_000;
+	pub const MaxCodeSize: u32 = 2 * 1024;
+	pub const MaxMetadataLen: u32 = 1024;
+	pub const MaxDebugBufferLen: u32 = 2 * 1024;
+}
+
+impl pallet_contracts::Config for Test {
+	type Time = Timestamp;
+	type Randomness = Randomness;
+	type Currency = Balances;
+	type Event = Event;
+	type RentPayment = ();
+	type SignedClaimHandicap = SignedClaimHandicap;
+	type TombstoneDeposit = TombstoneDeposit;
+	type DepositPerContract = DepositPerContract;
+	type DepositPerStorageByte = DepositPerStorageByte;
+	type DepositPerStorageItem = DepositPerStorageItem;
+	type RentFraction = ();
+	type SurchargeReward = SurchargeReward;
+	type RentFree = RentFree;
+	type MaxDepth = MaxDepth;
+	type MaxValueSize = MaxValueSize;
+	type WeightPrice = ();
+	type WeightInfo = ();
+	type ChainExtension = ();
+	type DeletionQueueDepth = DeletionQueueDepth;
+	type DeletionWeightLimit = DeletionWeightLimit;
+	type MaxCodeSize = MaxCodeSize;
+	type MaxMetadataLen = MaxMetadataLen;
+	type MaxDebugBufferLen = MaxDebugBufferLen;
+}
+
+parameter_types! {
+	pub const MaxBatchedCalls: u32 = 3;
+	pub const MaxScheduledWeight: Weight = 2 * GAS_LIMIT;
+	pub const MaxScheduledPerBlock: u32 = 3;
+	pub const MaxNameLength: u32 = 32;
+}
+
+impl pallet_sudo_contracts::Config for Test {
+	type Event = Event;
+	type WeightInfo = ();
+	type OffchainSignature = MultiSignature;
+	type OffchainPublic = MultiSigner;
+	type MaxBatchedCalls = MaxBatchedCalls;
+	type MaxScheduledWeight = MaxScheduledWeight;
+	type MaxScheduledPerBlock = MaxScheduledPerBlock;
+	type MaxNameLength = MaxNameLength;
+}
+
+/// The sudo key and therefore the only deployer at genesis.
+pub const ALICE: AccountId32 = AccountId32::new([1u8; 32]);
+pub const BOB: AccountId32 = AccountId32::new([2u8; 32]);
+pub const CHARLIE: AccountId32 = AccountId32::new([3u8; 32]);
+
+pub const GAS_LIMIT: Weight = 10_000_000_000;
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(ALICE, 1_000_000), (BOB, 1_000_000), (CHARLIE, 1_000_000)],
+	}.assimilate_storage(&mut t).unwrap();
+	pallet_contracts::GenesisConfig::<Test> {
+		current_schedule: Default::default(),
+	}.assimilate_storage(&mut t).unwrap();
+	pallet_sudo::GenesisConfig::<Test> {
+		key: ALICE,
+	}.assimilate_storage(&mut t).unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+/// Loads the wasm module of a .wat file under `fixtures/` along with its code hash.
+pub fn compile_module(fixture_name: &str) -> (Vec<u8>, H256) {
+	let fixture_path = ["fixtures/", fixture_name, ".wat"].concat();
+	let wasm_binary = wat::parse_file(fixture_path).unwrap();
+	let code_hash = BlakeTwo256::hash(&wasm_binary);
+	(wasm_binary, code_hash)
+}
+
+/// The last event deposited by any pallet.
+pub fn last_event() -> Event {
+	System::events().pop().expect("Event expected").event
+}
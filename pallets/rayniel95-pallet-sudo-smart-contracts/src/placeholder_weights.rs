@@ -0,0 +1,126 @@
+//! Placeholder weights for the wrapper extrinsics.
+//!
+//! These are NOT benchmark results. Each weight is a rough estimate made of a fixed execution
+//! time and the storage accesses of the extrinsic, with another term per component where the
+//! work grows with it. Replace them by running the benchmarks in `benchmarking.rs` on reference
+//! hardware:
+//!
+//! ```text
+//! ./target/release/node-template benchmark \
+//!     --chain=dev \
+//!     --steps=50 \
+//!     --repeat=20 \
+//!     --pallet=rayniel95_pallet_sudo_smart_contracts \
+//!     --extrinsic=* \
+//!     --execution=wasm \
+//!     --wasm-execution=compiled \
+//!     --heap-pages=4096 \
+//!     --output=./pallets/rayniel95-pallet-sudo-smart-contracts/src/weights.rs
+//! ```
+//!
+//! `relay_call` has no benchmark because it needs a signature of the runtime's
+//! `OffchainSignature` type. Its estimate includes the signature verification.
+
+use crate::weight_info::WeightInfo;
+use frame_support::{
+	traits::Get,
+	weights::{RuntimeDbWeight, Weight, constants::RocksDbWeight},
+};
+use sp_std::marker::PhantomData;
+
+/// Estimated weights using the database weights of the runtime.
+pub struct PlaceholderWeight<T>(PhantomData<T>);
+
+/// `time` plus the weight of `reads` reads and `writes` writes, all multiplied by `n`.
+fn estimate<D: Get<RuntimeDbWeight>>(
+	n: u32,
+	time: Weight,
+	reads: Weight,
+	writes: Weight,
+) -> Weight {
+	time.saturating_add(D::get().reads(reads))
+		.saturating_add(D::get().writes(writes))
+		.saturating_mul(n as Weight)
+}
+
+macro_rules! placeholder_weights {
+	($db:ty) => {
+		fn call() -> Weight {
+			estimate::<$db>(1, 40_000_000, 4, 1)
+		}
+		fn batch_call(n: u32) -> Weight {
+			estimate::<$db>(1, 20_000_000, 3, 1)
+				.saturating_add(estimate::<$db>(n, 30_000_000, 1, 0))
+		}
+		fn instantiate_with_code(c: u32) -> Weight {
+			estimate::<$db>(1, 50_000_000, 5, 2)
+				.saturating_add(estimate::<$db>(c, 4_000_000, 0, 0))
+		}
+		fn instantiate() -> Weight {
+			estimate::<$db>(1, 50_000_000, 5, 2)
+		}
+		fn update_schedule() -> Weight {
+			estimate::<$db>(1, 30_000_000, 3, 1)
+		}
+		fn upload_code(c: u32) -> Weight {
+			estimate::<$db>(1, 30_000_000, 3, 1)
+				.saturating_add(estimate::<$db>(c, 4_000_000, 0, 0))
+		}
+		fn claim_surcharge() -> Weight {
+			estimate::<$db>(1, 20_000_000, 1, 0)
+		}
+		fn set_code() -> Weight {
+			estimate::<$db>(1, 60_000_000, 6, 3)
+		}
+		fn set_default_quota() -> Weight {
+			estimate::<$db>(1, 20_000_000, 0, 1)
+		}
+		fn set_account_quota() -> Weight {
+			estimate::<$db>(1, 20_000_000, 0, 1)
+		}
+		fn set_access_control(a: u32) -> Weight {
+			estimate::<$db>(1, 30_000_000, 2, 1)
+				.saturating_add(estimate::<$db>(a, 100_000, 0, 0))
+		}
+		fn grant_access() -> Weight {
+			estimate::<$db>(1, 35_000_000, 3, 1)
+		}
+		fn revoke_access() -> Weight {
+			estimate::<$db>(1, 35_000_000, 3, 1)
+		}
+		fn schedule_call() -> Weight {
+			estimate::<$db>(1, 30_000_000, 2, 3)
+		}
+		fn cancel_scheduled_call() -> Weight {
+			estimate::<$db>(1, 25_000_000, 2, 2)
+		}
+		fn add_relayer() -> Weight {
+			estimate::<$db>(1, 20_000_000, 0, 1)
+		}
+		fn remove_relayer() -> Weight {
+			estimate::<$db>(1, 25_000_000, 1, 1)
+		}
+		fn relay_call(d: u32) -> Weight {
+			estimate::<$db>(1, 110_000_000, 7, 2)
+				.saturating_add(estimate::<$db>(d, 2_000_000, 0, 0))
+		}
+		fn register_name() -> Weight {
+			estimate::<$db>(1, 45_000_000, 2, 3)
+		}
+		fn publish_name_version() -> Weight {
+			estimate::<$db>(1, 45_000_000, 3, 3)
+		}
+		fn transfer_name() -> Weight {
+			estimate::<$db>(1, 30_000_000, 2, 1)
+		}
+	};
+}
+
+impl<T: frame_system::Config> WeightInfo for PlaceholderWeight<T> {
+	placeholder_weights!(T::DbWeight);
+}
+
+// For tests
+impl WeightInfo for () {
+	placeholder_weights!(RocksDbWeight);
+}
@@ -0,0 +1,173 @@
+//! Per-account execution quotas.
+//!
+//! Calls through the wrapper are feeless, so quotas take the place of fees as spam protection.
+//! Every account has a budget of weight and calls which is refilled once per period. The budget
+//! is checked when a transaction enters the pool by [`CheckQuota`] and again at dispatch, where
+//! the weight actually used is recorded.
+
+use crate::{Call, Config, Pallet};
+use codec::{Encode, Decode};
+use frame_support::{traits::IsSubType, weights::{DispatchInfo, Weight}};
+#[cfg(feature = "std")]
+use serde::{Serialize, Deserialize};
+use sp_core::crypto::UncheckedFrom;
+use sp_runtime::{
+	RuntimeDebug,
+	traits::{DispatchInfoOf, Dispatchable, SignedExtension},
+	transaction_validity::{
+		InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+	},
+};
+use sp_std::{fmt, marker::PhantomData};
+
+/// Custom [`InvalidTransaction`] code used when the sender has exhausted its quota.
+pub const QUOTA_EXCEEDED: u8 = 1;
+
+/// The budget of an account per period.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct Quota<BlockNumber> {
+	/// The weight an account may consume per period.
+	pub max_weight: Weight,
+	/// The number of calls an account may make per period.
+	pub max_calls: u32,
+	/// The length of a period in blocks. Must not be zero.
+	pub period: BlockNumber,
+}
+
+/// What an account consumed in its current period.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct QuotaUsage<BlockNumber> {
+	/// The block at which the current period started.
+	pub period_start: BlockNumber,
+	/// The weight consumed in the current period.
+	pub weight: Weight,
+	/// The calls made in the current period.
+	pub calls: u32,
+}
+
+/// What is left of the budget of an account in its current period.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct RemainingQuota<BlockNumber> {
+	/// The weight that can still be consumed.
+	pub weight: Weight,
+	/// The calls that can still be made.
+	pub calls: u32,
+	/// The block at which the budget is refilled.
+	pub resets_at: BlockNumber,
+}
+
+impl<T: Config> Call<T>
+where
+	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>,
+{
+	/// The weight that is counted against the quota of [`Self::quota_account`] when
+	/// dispatching this call.
+	///
+	/// Returns `None` for calls that are not subject to quotas.
+	pub fn quota_weight(&self) -> Option<Weight> {
+		match self {
+			Call::call(_, _, gas_limit, _) => Some(*gas_limit),
+			Call::batch_call(calls) => Some(
+				calls.iter().fold(0, |weight: Weight, call| weight.saturating_add(call.2))
+			),
+			Call::instantiate_with_code(_, gas_limit, _, _, _, _) => Some(*gas_limit),
+			Call::instantiate(_, gas_limit, _, _, _) => Some(*gas_limit),
+			Call::upload_code(_, _) => Some(0),
+			Call::relay_call(_, call, _) => Some(call.gas_limit),
+			_ => None,
+		}
+	}
+
+	/// The number of calls that are counted against the quota of [`Self::quota_account`].
+	///
+	/// Every call of a batch counts, an empty batch as one call.
+	pub fn quota_calls(&self) -> u32 {
+		match self {
+			Call::batch_call(calls) => (calls.len() as u32).max(1),
+			_ => 1,
+		}
+	}
+
+	/// The account whose quota is charged when `who` dispatches this call.
+	///
+	/// This is the signer of a relayed call and `who` for all other calls.
+	pub fn quota_account<'a>(&'a self, who: &'a T::AccountId) -> &'a T::AccountId {
+		match self {
+			Call::relay_call(signer, _, _) => signer,
+			_ => who,
+		}
+	}
+}
+
+/// Rejects transactions of senders that have exhausted their quota before they enter the pool.
+#[derive(Encode, Decode, Clone, Eq, PartialEq)]
+pub struct CheckQuota<T: Config + Send + Sync>(PhantomData<T>);
+
+impl<T: Config + Send + Sync> CheckQuota<T> {
+	/// Create a new instance of the extension.
+	pub fn new() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<T: Config + Send + Sync> Default for CheckQuota<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T: Config + Send + Sync> fmt::Debug for CheckQuota<T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "CheckQuota")
+	}
+}
+
+impl<T: Config + Send + Sync> SignedExtension for CheckQuota<T>
+where
+	<T as frame_system::Config>::Call: IsSubType<Call<T>> + Dispatchable<Info = DispatchInfo>,
+	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>,
+{
+	const IDENTIFIER: &'static str = "CheckQuota";
+	type AccountId = T::AccountId;
+	type Call = <T as frame_system::Config>::Call;
+	type AdditionalSigned = ();
+	type Pre = ();
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> TransactionValidity {
+		// The same measure as at dispatch so that the pool admits exactly what dispatch does.
+		let (account, calls, weight) = match call.is_sub_type() {
+			Some(call) => match call.quota_weight() {
+				Some(weight) => (call.quota_account(who), call.quota_calls(), weight),
+				None => return Ok(ValidTransaction::default()),
+			},
+			None => return Ok(ValidTransaction::default()),
+		};
+		<Pallet<T>>::ensure_quota_for(account, calls, weight)
+			.map_err(|_| InvalidTransaction::Custom(QUOTA_EXCEEDED))?;
+		Ok(ValidTransaction::default())
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		self.validate(who, call, info, len).map(|_| ())
+	}
+}
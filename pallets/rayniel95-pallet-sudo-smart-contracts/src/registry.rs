@@ -0,0 +1,156 @@
+//! A registry of human-readable contract names.
+//!
+//! Names have the form `namespace/name`, e.g. `org/payments`, and consist of lowercase ASCII
+//! letters, digits, `-`, `_` and `.`. Every name has an owner and a history of versions, each
+//! pointing to a contract and the code hash it had when the version was published. A reference
+//! like `org/payments@v2` resolves to the second version, `org/payments` to the latest one.
+//! Resolving also reports the current code hash of the contract, which differs from the
+//! published one once the code was replaced with `set_code`.
+
+use crate::{CodeHash, Config, ContractNames, NameVersions, Names, Pallet};
+use codec::{Encode, Decode};
+#[cfg(feature = "std")]
+use serde::{Serialize, Deserialize};
+use sp_runtime::RuntimeDebug;
+use sp_std::vec::Vec;
+
+/// Separates the namespace from the rest of a name.
+pub const NAMESPACE_SEPARATOR: u8 = b'/';
+
+/// Separates a name from the version in a reference like `org/payments@v2`.
+pub const VERSION_SEPARATOR: u8 = b'@';
+
+/// A registered name.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct NameRecord<AccountId> {
+	/// The account that may publish new versions of the name and transfer it.
+	pub owner: AccountId,
+	/// The number of the latest version. Versions are numbered from 1.
+	pub latest: u32,
+}
+
+/// A version of a registered name.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct NameVersion<AccountId, Hash, BlockNumber> {
+	/// The contract the version points to.
+	pub address: AccountId,
+	/// The code hash of the contract when the version was published.
+	pub code_hash: Hash,
+	/// The block in which the version was published.
+	pub published_at: BlockNumber,
+}
+
+/// What a name reference resolves to.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct ResolvedName<AccountId, Hash, BlockNumber> {
+	/// The owner of the name.
+	pub owner: AccountId,
+	/// The number of the resolved version.
+	pub version: u32,
+	/// The contract the version points to.
+	pub address: AccountId,
+	/// The current code hash of the contract.
+	pub code_hash: Hash,
+	/// The code hash of the contract when the version was published.
+	pub published_code_hash: Hash,
+	/// The block in which the version was published.
+	pub published_at: BlockNumber,
+}
+
+/// Whether `name` is a well-formed `namespace/name` without a version.
+pub fn is_valid_name(name: &[u8]) -> bool {
+	let mut parts = name.split(|byte| *byte == NAMESPACE_SEPARATOR);
+	let valid_part = |part: Option<&[u8]>| part.map_or(false, |part| {
+		!part.is_empty() && part.iter().all(|byte| matches!(
+			byte, b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.'
+		))
+	});
+	valid_part(parts.next()) && valid_part(parts.next()) && parts.next().is_none()
+}
+
+/// Splits a reference like `org/payments@v2` into the name and the version.
+///
+/// Returns `None` if the version is not of the form `v<number>` with a non-zero number.
+pub fn parse_reference(reference: &[u8]) -> Option<(&[u8], Option<u32>)> {
+	let at = match reference.iter().position(|byte| *byte == VERSION_SEPARATOR) {
+		Some(at) => at,
+		None => return Some((reference, None)),
+	};
+	let digits = match reference[at + 1..].split_first() {
+		Some((b'v', digits)) => digits,
+		_ => return None,
+	};
+	if digits.is_empty() || digits[0] == b'0' {
+		return None;
+	}
+	let version = digits.iter().try_fold(0u32, |version, byte| match byte {
+		b'0'..=b'9' => version.checked_mul(10)?.checked_add((byte - b'0') as u32),
+		_ => None,
+	})?;
+	Some((&reference[..at], Some(version)))
+}
+
+/// Formats `name` and `version` as a reference like `org/payments@v2`.
+pub fn format_reference(name: &[u8], version: u32) -> Vec<u8> {
+	let mut reference = name.to_vec();
+	reference.push(VERSION_SEPARATOR);
+	reference.push(b'v');
+	let start = reference.len();
+	let mut rest = version;
+	loop {
+		reference.insert(start, b'0' + (rest % 10) as u8);
+		rest /= 10;
+		if rest == 0 {
+			return reference;
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Resolves a reference like `org/payments@v2` or `org/payments`.
+	///
+	/// Returns `None` if the contract of the version no longer exists.
+	pub fn resolve(
+		reference: &[u8],
+	) -> Option<ResolvedName<T::AccountId, CodeHash<T>, T::BlockNumber>> {
+		let (name, version) = parse_reference(reference)?;
+		let record = <Names<T>>::get(name)?;
+		let version = version.unwrap_or(record.latest);
+		let NameVersion { address, code_hash: published_code_hash, published_at } =
+			<NameVersions<T>>::get(name, version)?;
+		let code_hash = pallet_contracts::Pallet::<T>::code_hash(&address)?;
+		Some(ResolvedName {
+			owner: record.owner,
+			version,
+			address,
+			code_hash,
+			published_code_hash,
+			published_at,
+		})
+	}
+
+	/// Returns the reference of the latest version published for `contract`, e.g.
+	/// `org/payments@v2`.
+	pub fn name_of(contract: &T::AccountId) -> Option<Vec<u8>> {
+		<ContractNames<T>>::get(contract).map(|(name, version)| format_reference(&name, version))
+	}
+
+	/// Publishes `contract` as the next version of `name` and returns its number.
+	pub(crate) fn publish_version(
+		name: &[u8],
+		record: &mut NameRecord<T::AccountId>,
+		contract: T::AccountId,
+		code_hash: CodeHash<T>,
+	) -> u32 {
+		record.latest = record.latest.saturating_add(1);
+		<NameVersions<T>>::insert(name, record.latest, NameVersion {
+			address: contract.clone(),
+			code_hash,
+			published_at: <frame_system::Pallet<T>>::block_number(),
+		});
+		<ContractNames<T>>::insert(contract, (name.to_vec(), record.latest));
+		record.latest
+	}
+}
@@ -0,0 +1,29 @@
+//! Contract calls signed off-chain and submitted by a relayer.
+//!
+//! Users that never talk to the node sign a [`RelayedCall`] and hand it to a relayer, which
+//! submits it through [`Pallet::relay_call`](crate::Pallet::relay_call). The contract is
+//! called with the signer as its caller. Every payload carries the next nonce of its signer,
+//! an expiry block and the genesis hash of the chain, so it cannot be replayed on this or on
+//! another chain.
+
+use codec::{Encode, Decode};
+use frame_support::weights::Weight;
+use sp_runtime::RuntimeDebug;
+use sp_std::vec::Vec;
+
+/// The payload a user signs to have a contract called on their behalf.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct RelayedCall<AccountId, BlockNumber, Hash> {
+	/// The contract to call.
+	pub dest: AccountId,
+	/// The input data passed to the contract.
+	pub data: Vec<u8>,
+	/// The gas limit of the call.
+	pub gas_limit: Weight,
+	/// The nonce of the signer. Must equal the number of calls relayed for them so far.
+	pub nonce: u64,
+	/// The last block in which the payload can be submitted.
+	pub expiry: BlockNumber,
+	/// The hash of the genesis block of the chain the payload is meant for.
+	pub genesis_hash: Hash,
+}
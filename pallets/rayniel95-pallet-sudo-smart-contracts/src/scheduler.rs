@@ -1,7 +1,157 @@
-use pallet_contracts::{Config, Schedule};
-// TODO - extend the pallet_contracts crate with this fucntion
-// pub fn schedule<T: Config>()-> Schedule<T>{
-  
+//! Scheduled and recurring contract calls.
+//!
+//! Root registers calls that are made on behalf of an account at a given block and, if they
+//! are periodic, again every `period` blocks until they ran `max_runs` times or are cancelled.
+//! Due calls are made in `on_initialize`. Calls that do not fit into the weight budget of a
+//! block are deferred to the next block. No agenda holds more than `MaxScheduledPerBlock`
+//! calls; calls that would overflow an agenda spill over to the first later block with room.
 
-//     schedule
-// }
\ No newline at end of file
+use crate::{Agenda, Config, Event, Pallet, ScheduledCalls};
+use codec::{Encode, Decode};
+use frame_support::{traits::Get, weights::Weight};
+use sp_core::crypto::UncheckedFrom;
+use sp_runtime::{RuntimeDebug, traits::{One, Saturating, Zero}};
+use sp_std::vec::Vec;
+
+/// A contract call registered through [`Pallet::schedule_call`].
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct ScheduledCall<AccountId, BlockNumber> {
+	/// The account on whose behalf the call is made.
+	pub origin: AccountId,
+	/// The contract to call.
+	pub dest: AccountId,
+	/// The input data passed to the contract.
+	pub data: Vec<u8>,
+	/// The gas limit of every run.
+	pub gas_limit: Weight,
+	/// The block at which the call is made next.
+	pub next_run: BlockNumber,
+	/// The number of blocks between two runs or `None` for a one-shot call.
+	pub period: Option<BlockNumber>,
+	/// The number of runs after which a periodic call is removed or `None` to run it until
+	/// it is cancelled.
+	pub max_runs: Option<u32>,
+	/// The number of runs so far.
+	pub runs: u32,
+}
+
+impl<AccountId, BlockNumber> ScheduledCall<AccountId, BlockNumber>
+where
+	BlockNumber: Copy + Saturating,
+{
+	/// The block of the run after the one made at `now` or `None` if this was the last run.
+	fn reschedule(&self, now: BlockNumber) -> Option<BlockNumber> {
+		let period = self.period?;
+		match self.max_runs {
+			Some(max_runs) if self.runs >= max_runs => None,
+			_ => Some(now.saturating_add(period)),
+		}
+	}
+}
+
+impl<T: Config> Pallet<T>
+where
+	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>,
+{
+	/// Makes the calls due at `now` as long as their gas limits fit into
+	/// `T::MaxScheduledWeight`, deferring the rest to the next block.
+	///
+	/// Returns the weight consumed.
+	pub(crate) fn service_agenda(now: T::BlockNumber) -> Weight {
+		let db_weight = T::DbWeight::get();
+		let agenda = <Agenda<T>>::take(now);
+		let mut weight = db_weight.reads_writes(1, 1);
+		if agenda.is_empty() {
+			return weight;
+		}
+
+		let budget = T::MaxScheduledWeight::get();
+		let mut used: Weight = 0;
+		let mut deferred = Vec::new();
+		for id in agenda {
+			weight = weight.saturating_add(db_weight.reads(1));
+			let mut scheduled = match <ScheduledCalls<T>>::get(id) {
+				Some(scheduled) => scheduled,
+				None => continue,
+			};
+			if !deferred.is_empty() || used.saturating_add(scheduled.gas_limit) > budget {
+				deferred.push(id);
+				continue;
+			}
+
+			let (result, output) = pallet_contracts::Pallet::<T>::call_with_output(
+				scheduled.origin.clone(),
+				scheduled.dest.clone(),
+				Zero::zero(),
+				scheduled.gas_limit,
+				scheduled.data.clone(),
+			);
+			let outcome = Self::outcome(&result, output.as_ref(), scheduled.gas_limit);
+			used = used.saturating_add(scheduled.gas_limit);
+			weight = weight.saturating_add(outcome.gas_consumed);
+			scheduled.runs = scheduled.runs.saturating_add(1);
+			Self::deposit_event(Event::ScheduledCallExecuted(id, scheduled.runs, outcome));
+
+			weight = weight.saturating_add(db_weight.writes(2));
+			match scheduled.reschedule(now) {
+				Some(next_run) => {
+					let (next_run, reads) = Self::append_to_agenda(next_run, id);
+					weight = weight.saturating_add(db_weight.reads(reads));
+					scheduled.next_run = next_run;
+					<ScheduledCalls<T>>::insert(id, scheduled);
+				},
+				None => {
+					<ScheduledCalls<T>>::remove(id);
+					Self::deposit_event(Event::ScheduledCallCompleted(id));
+				},
+			}
+		}
+
+		if !deferred.is_empty() {
+			// Deferred calls go first, the calls that no longer fit spill over to later blocks.
+			let next = now.saturating_add(One::one());
+			let deferred_count = deferred.len();
+			deferred.extend(<Agenda<T>>::get(next));
+			let max = T::MaxScheduledPerBlock::get() as usize;
+			let spilled = deferred.split_off(deferred.len().min(max));
+			let mut reads = 1;
+			for id in deferred.iter().take(deferred_count) {
+				Self::set_next_run(*id, next);
+			}
+			for id in &spilled {
+				let (block, agenda_reads) =
+					Self::append_to_agenda(next.saturating_add(One::one()), *id);
+				Self::set_next_run(*id, block);
+				reads += agenda_reads;
+			}
+			let writes = (deferred_count + spilled.len() * 2) as Weight + 1;
+			<Agenda<T>>::insert(next, deferred);
+			weight = weight.saturating_add(db_weight.reads_writes(
+				reads as Weight + writes, writes,
+			));
+		}
+		weight
+	}
+
+	/// Appends `id` to the agenda of the first block from `block` on that has room for it.
+	///
+	/// Returns that block and the number of agendas read to find it.
+	fn append_to_agenda(mut block: T::BlockNumber, id: u64) -> (T::BlockNumber, u32) {
+		let max = T::MaxScheduledPerBlock::get() as usize;
+		let mut reads = 1;
+		while <Agenda<T>>::decode_len(block).unwrap_or(0) >= max {
+			block = block.saturating_add(One::one());
+			reads += 1;
+		}
+		<Agenda<T>>::append(block, id);
+		(block, reads)
+	}
+
+	fn set_next_run(id: u64, block: T::BlockNumber) {
+		<ScheduledCalls<T>>::mutate(id, |scheduled| {
+			if let Some(scheduled) = scheduled {
+				scheduled.next_run = block;
+			}
+		});
+	}
+}
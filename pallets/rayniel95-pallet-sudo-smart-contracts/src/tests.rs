@@ -1,23 +1,934 @@
-use crate::{Error, mock::*};
-use frame_support::{assert_ok, assert_noop};
+use crate::{
+	Agenda, CheckQuota, Error, Event as SudoContractsEvent, ExecutionOutcome, Quota, RelayedCall,
+	RemainingQuota, mock::*, quota::QUOTA_EXCEEDED, registry, weight_info::WeightInfo,
+};
+use codec::Encode;
+use frame_support::{
+	assert_ok, assert_noop,
+	traits::OnInitialize,
+	weights::{GetDispatchInfo, Pays},
+};
+use pallet_contracts::Schedule;
+use sp_core::{H256, Pair, sr25519};
+use sp_runtime::{
+	AccountId32, DispatchError, DispatchResult, MultiSignature, MultiSigner,
+	traits::{BlakeTwo256, Hash, IdentifyAccount, SignedExtension},
+	transaction_validity::InvalidTransaction,
+};
+
+fn last_outcome() -> ExecutionOutcome<H256> {
+	match last_event() {
+		Event::pallet_sudo_contracts(SudoContractsEvent::ContractCalled(_, _, outcome)) |
+		Event::pallet_sudo_contracts(
+			SudoContractsEvent::ContractInstantiated(_, _, _, outcome)
+		) => outcome,
+		event => panic!("Unexpected event: {:?}", event),
+	}
+}
+
+fn last_result() -> DispatchResult {
+	last_outcome().result
+}
+
+/// The `(id, run, result)` of every scheduled call made so far.
+fn scheduled_runs() -> Vec<(u64, u32, DispatchResult)> {
+	System::events().into_iter()
+		.filter_map(|record| match record.event {
+			Event::pallet_sudo_contracts(
+				SudoContractsEvent::ScheduledCallExecuted(id, run, outcome)
+			) => Some((id, run, outcome.result)),
+			_ => None,
+		})
+		.collect()
+}
+
+fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		System::set_block_number(System::block_number() + 1);
+		SudoContracts::on_initialize(System::block_number());
+	}
+}
+
+/// A key pair that signs relayed calls and the account it controls.
+fn relay_signer() -> (sr25519::Pair, AccountId32) {
+	let pair = sr25519::Pair::from_seed(&[7u8; 32]);
+	let account = MultiSigner::from(pair.public()).into_account();
+	(pair, account)
+}
+
+fn relayed_call(dest: AccountId32, nonce: u64) -> RelayedCall<AccountId32, u64, H256> {
+	RelayedCall {
+		dest,
+		data: vec![],
+		gas_limit: GAS_LIMIT,
+		nonce,
+		expiry: 10,
+		genesis_hash: System::block_hash(0),
+	}
+}
+
+fn sign(pair: &sr25519::Pair, call: &RelayedCall<AccountId32, u64, H256>) -> MultiSignature {
+	pair.sign(&call.encode()).into()
+}
+
+fn deploy(fixture_name: &str) -> AccountId32 {
+	let (wasm, code_hash) = compile_module(fixture_name);
+	assert_ok!(SudoContracts::instantiate_with_code(
+		Origin::signed(ALICE), 100, GAS_LIMIT, wasm, vec![], vec![], None,
+	));
+	assert_eq!(last_result(), Ok(()));
+	Contracts::contract_address(&ALICE, &code_hash, &[])
+}
+
+#[test]
+fn sudo_key_instantiates_and_owns_contract() {
+	let (wasm, code_hash) = compile_module("dummy");
+	new_test_ext().execute_with(|| {
+		let post_info = SudoContracts::instantiate_with_code(
+			Origin::signed(ALICE), 100, GAS_LIMIT, wasm, vec![], vec![], None,
+		).unwrap();
+		assert_eq!(post_info.pays_fee, Pays::No);
+
+		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+		match last_event() {
+			Event::pallet_sudo_contracts(SudoContractsEvent::ContractInstantiated(
+				deployer, contract, hash, outcome,
+			)) => {
+				assert_eq!((deployer, contract, hash), (ALICE, addr.clone(), code_hash));
+				assert_eq!(outcome.flags, Some(0));
+				assert_eq!(outcome.result, Ok(()));
+			},
+			event => panic!("Unexpected event: {:?}", event),
+		}
+		assert_eq!(SudoContracts::contract_owner(&addr), Some(ALICE));
+	});
+}
+
+#[test]
+fn only_deployers_can_instantiate() {
+	let (wasm, _) = compile_module("dummy");
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			SudoContracts::instantiate_with_code(
+				Origin::signed(BOB), 100, GAS_LIMIT, wasm.clone(), vec![], vec![], None,
+			),
+			pallet_contracts::Error::<Test>::NotAuthorizedDeployer,
+		);
+
+		assert_ok!(Contracts::add_deployer(Origin::root(), BOB, None));
+		assert_ok!(SudoContracts::instantiate_with_code(
+			Origin::signed(BOB), 100, GAS_LIMIT, wasm, vec![], vec![], None,
+		));
+		assert_eq!(last_result(), Ok(()));
+	});
+}
+
+#[test]
+fn root_cannot_be_the_instantiator() {
+	let (wasm, _) = compile_module("dummy");
+	new_test_ext().execute_with(|| {
+		// Root passes the gate but a contract needs a signed account to be derived from.
+		assert_noop!(
+			SudoContracts::instantiate_with_code(
+				Origin::root(), 100, GAS_LIMIT, wasm, vec![], vec![], None,
+			),
+			DispatchError::BadOrigin,
+		);
+	});
+}
+
+#[test]
+fn failed_instantiation_records_no_owner() {
+	let (wasm, code_hash) = compile_module("trap_in_deploy");
+	new_test_ext().execute_with(|| {
+		assert_ok!(SudoContracts::instantiate_with_code(
+			Origin::signed(ALICE), 100, GAS_LIMIT, wasm, vec![], vec![], None,
+		));
+		assert_eq!(
+			last_result(),
+			Err(pallet_contracts::Error::<Test>::ContractTrapped.into()),
+		);
+
+		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+		assert_eq!(SudoContracts::contract_owner(&addr), None);
+	});
+}
+
+#[test]
+fn call_is_feeless() {
+	new_test_ext().execute_with(|| {
+		let addr = deploy("dummy");
+
+		let post_info = SudoContracts::call(Origin::signed(BOB), addr, 0, GAS_LIMIT, vec![])
+			.unwrap();
+		assert_eq!(post_info.pays_fee, Pays::No);
+		assert_eq!(last_result(), Ok(()));
+		assert_eq!(Balances::free_balance(&BOB), 1_000_000);
+	});
+}
+
+#[test]
+fn call_refunds_unused_gas() {
+	new_test_ext().execute_with(|| {
+		let addr = deploy("dummy");
+
+		let call = crate::Call::<Test>::call(addr.clone(), 0, GAS_LIMIT, vec![]);
+		let declared = call.get_dispatch_info().weight;
+		let post_info = SudoContracts::call(Origin::signed(BOB), addr, 0, GAS_LIMIT, vec![])
+			.unwrap();
+		let actual = post_info.actual_weight.unwrap();
+		assert_eq!(actual, <() as WeightInfo>::call() + last_outcome().gas_consumed);
+		assert!(actual < declared);
+		assert!(declared > GAS_LIMIT);
+	});
+}
+
+#[test]
+fn call_event_reports_output() {
+	new_test_ext().execute_with(|| {
+		let addr = deploy("revert_with_data");
+
+		let post_info = SudoContracts::call(Origin::signed(BOB), addr.clone(), 0, GAS_LIMIT, vec![])
+			.unwrap();
+		assert_eq!(
+			last_event(),
+			Event::pallet_sudo_contracts(SudoContractsEvent::ContractCalled(
+				BOB,
+				addr,
+				ExecutionOutcome {
+					gas_consumed: post_info.actual_weight.unwrap() - <() as WeightInfo>::call(),
+					flags: Some(1),
+					output_hash: Some(BlakeTwo256::hash(&[1, 2, 3, 4])),
+					result: Ok(()),
+				},
+			)),
+		);
+	});
+}
+
+#[test]
+fn failing_call_is_reported_in_event() {
+	new_test_ext().execute_with(|| {
+		let addr = deploy("run_out_of_gas");
+
+		assert_ok!(SudoContracts::call(Origin::signed(BOB), addr, 0, 1_000_000_000, vec![]));
+		let outcome = last_outcome();
+		assert_eq!(outcome.result, Err(pallet_contracts::Error::<Test>::OutOfGas.into()));
+		assert_eq!((outcome.flags, outcome.output_hash), (None, None));
+		assert!(outcome.gas_consumed >= 1_000_000_000);
+
+		assert_ok!(SudoContracts::call(Origin::signed(BOB), CHARLIE, 0, GAS_LIMIT, vec![]));
+		assert_eq!(last_result(), Err(pallet_contracts::Error::<Test>::NotCallable.into()));
+	});
+}
+
+#[test]
+fn paused_contract_call_is_reported_in_event() {
+	new_test_ext().execute_with(|| {
+		let addr = deploy("dummy");
+
+		assert_ok!(Contracts::pause_contract(Origin::root(), addr.clone()));
+		assert_ok!(SudoContracts::call(Origin::signed(BOB), addr.clone(), 0, GAS_LIMIT, vec![]));
+		assert_eq!(last_result(), Err(pallet_contracts::Error::<Test>::ContractPaused.into()));
+
+		assert_ok!(Contracts::unpause_contract(Origin::root(), addr.clone()));
+		assert_ok!(SudoContracts::call(Origin::signed(BOB), addr, 0, GAS_LIMIT, vec![]));
+		assert_eq!(last_result(), Ok(()));
+	});
+}
+
+#[test]
+fn batch_call_commits_all_calls() {
+	new_test_ext().execute_with(|| {
+		let dummy = deploy("dummy");
+
+		let post_info = SudoContracts::batch_call(Origin::signed(BOB), vec![
+			(dummy.clone(), 100, GAS_LIMIT, vec![]),
+			(dummy.clone(), 200, GAS_LIMIT, vec![]),
+		]).unwrap();
+		assert_eq!(post_info.pays_fee, Pays::No);
+		assert_eq!(
+			last_event(),
+			Event::pallet_sudo_contracts(SudoContractsEvent::BatchCompleted(BOB, 2)),
+		);
+		assert_eq!(Balances::free_balance(&dummy), 100 + 300);
+	});
+}
+
+#[test]
+fn batch_call_rolls_back_on_revert() {
+	new_test_ext().execute_with(|| {
+		let dummy = deploy("dummy");
+		let reverting = {
+			let (wasm, code_hash) = compile_module("revert_with_data");
+			assert_ok!(SudoContracts::instantiate_with_code(
+				Origin::signed(ALICE), 100, GAS_LIMIT, wasm, vec![], vec![], None,
+			));
+			Contracts::contract_address(&ALICE, &code_hash, &[])
+		};
+
+		assert_ok!(SudoContracts::batch_call(Origin::signed(BOB), vec![
+			(dummy.clone(), 100, GAS_LIMIT, vec![]),
+			(reverting, 0, GAS_LIMIT, vec![]),
+		]));
+		assert_eq!(
+			last_event(),
+			Event::pallet_sudo_contracts(SudoContractsEvent::BatchInterrupted(
+				BOB, 1, Error::<Test>::ContractReverted.into(),
+			)),
+		);
+		assert_eq!(Balances::free_balance(&dummy), 100);
+		assert_eq!(Balances::free_balance(&BOB), 1_000_000);
+	});
+}
+
+#[test]
+fn batch_call_rolls_back_on_trap() {
+	new_test_ext().execute_with(|| {
+		let dummy = deploy("dummy");
+
+		assert_ok!(SudoContracts::batch_call(Origin::signed(BOB), vec![
+			(dummy.clone(), 100, GAS_LIMIT, vec![]),
+			(CHARLIE, 0, GAS_LIMIT, vec![]),
+		]));
+		assert_eq!(
+			last_event(),
+			Event::pallet_sudo_contracts(SudoContractsEvent::BatchInterrupted(
+				BOB, 1, pallet_contracts::Error::<Test>::NotCallable.into(),
+			)),
+		);
+		assert_eq!(Balances::free_balance(&dummy), 100);
+	});
+}
+
+#[test]
+fn batch_call_is_checked_up_front() {
+	new_test_ext().execute_with(|| {
+		let dummy = deploy("dummy");
+
+		assert_noop!(
+			SudoContracts::batch_call(
+				Origin::signed(BOB),
+				vec![(dummy.clone(), 0, GAS_LIMIT, vec![]); 4],
+			),
+			Error::<Test>::TooManyCalls,
+		);
+
+		assert_ok!(SudoContracts::grant_access(Origin::signed(ALICE), dummy.clone(), CHARLIE, None));
+		assert_noop!(
+			SudoContracts::batch_call(Origin::signed(BOB), vec![(dummy, 0, GAS_LIMIT, vec![])]),
+			Error::<Test>::CallerNotAllowed,
+		);
+	});
+}
 
 #[test]
-fn it_works_for_default_value() {
+fn bare_call_applies_access_control() {
 	new_test_ext().execute_with(|| {
-		// Dispatch a signed extrinsic.
-		assert_ok!(TemplateModule::do_something(Origin::signed(1), 42));
-		// Read pallet storage and assert an expected result.
-		assert_eq!(TemplateModule::something(), Some(42));
+		let dummy = deploy("dummy");
+
+		let result = SudoContracts::bare_call(BOB, dummy.clone(), 0, GAS_LIMIT, vec![]);
+		assert!(result.result.unwrap().is_success());
+		assert!(result.gas_consumed > 0);
+
+		assert_ok!(SudoContracts::grant_access(Origin::signed(ALICE), dummy.clone(), CHARLIE, None));
+		let result = SudoContracts::bare_call(BOB, dummy.clone(), 0, GAS_LIMIT, vec![]);
+		assert_eq!(result.result, Err(Error::<Test>::CallerNotAllowed.into()));
+		assert_eq!(result.gas_consumed, 0);
+		assert!(SudoContracts::bare_call(CHARLIE, dummy, 0, GAS_LIMIT, vec![]).result.is_ok());
+	});
+}
+
+#[test]
+fn bare_call_returns_debug_message() {
+	new_test_ext().execute_with(|| {
+		let contract = deploy("debug_message");
+
+		let result = SudoContracts::bare_call(BOB, contract.clone(), 0, GAS_LIMIT, vec![]);
+		assert!(result.result.unwrap().is_success());
+		assert_eq!(result.debug_message.0, b"Hello World!".to_vec());
+
+		// Messages are dropped when executing on chain.
+		assert_ok!(SudoContracts::call(Origin::signed(BOB), contract, 0, GAS_LIMIT, vec![]));
+	});
+}
+
+#[test]
+fn bare_call_traced_records_frames() {
+	let (_, code_hash) = compile_module("debug_message");
+	new_test_ext().execute_with(|| {
+		let contract = deploy("debug_message");
+
+		let (result, frames) =
+			SudoContracts::bare_call_traced(BOB, contract.clone(), 0, GAS_LIMIT, vec![1, 2]);
+		assert!(result.result.unwrap().is_success());
+		assert_eq!(result.debug_message.0, b"Hello World!".to_vec());
+		assert_eq!(frames.len(), 1);
+		assert_eq!((&frames[0].caller, &frames[0].callee), (&BOB, &contract));
+		assert_eq!(frames[0].code_hash, Some(code_hash));
+		assert_eq!(frames[0].input.0, vec![1, 2]);
+		assert_eq!(frames[0].gas_used, result.gas_consumed);
+
+		assert_ok!(SudoContracts::grant_access(Origin::signed(ALICE), contract.clone(), CHARLIE, None));
+		let (result, frames) = SudoContracts::bare_call_traced(BOB, contract, 0, GAS_LIMIT, vec![]);
+		assert_eq!(result.result, Err(Error::<Test>::CallerNotAllowed.into()));
+		assert!(frames.is_empty());
 	});
 }
 
 #[test]
-fn correct_error_for_none_value() {
+fn set_code_swaps_code_of_live_contract() {
+	let (dummy_wasm, dummy_hash) = compile_module("dummy");
+	let (wasm, code_hash) = compile_module("revert_with_data");
 	new_test_ext().execute_with(|| {
-		// Ensure the expected error is thrown when no value is present.
+		let addr = deploy("dummy");
+
+		assert_noop!(
+			SudoContracts::set_code(Origin::signed(ALICE), addr.clone(), code_hash),
+			pallet_contracts::Error::<Test>::CodeNotFound,
+		);
+		assert_ok!(SudoContracts::upload_code(Origin::signed(ALICE), wasm, None));
+		assert_noop!(
+			SudoContracts::set_code(Origin::signed(ALICE), addr.clone(), code_hash),
+			pallet_contracts::Error::<Test>::CodeNotApproved,
+		);
+		assert_ok!(Contracts::approve_code(Origin::root(), code_hash));
+		assert_noop!(
+			SudoContracts::set_code(Origin::signed(BOB), addr.clone(), code_hash),
+			Error::<Test>::NotContractOwner,
+		);
+
+		assert_ok!(SudoContracts::set_code(Origin::signed(ALICE), addr.clone(), code_hash));
+		assert_eq!(
+			last_event(),
+			Event::pallet_sudo_contracts(
+				SudoContractsEvent::CodeUpgraded(addr.clone(), dummy_hash, code_hash)
+			),
+		);
+
+		// The contract now runs the new code and the unused old code was removed.
+		assert_ok!(SudoContracts::call(Origin::signed(BOB), addr, 0, GAS_LIMIT, vec![]));
+		assert_eq!(last_outcome().flags, Some(1));
+		assert_ok!(Contracts::upload_code(Origin::signed(ALICE), dummy_wasm));
+	});
+}
+
+#[test]
+fn metadata_is_attached_to_uploaded_code() {
+	let (dummy_wasm, dummy_hash) = compile_module("dummy");
+	let (wasm, code_hash) = compile_module("revert_with_data");
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			SudoContracts::upload_code(Origin::signed(BOB), wasm.clone(), None),
+			pallet_contracts::Error::<Test>::NotAuthorizedDeployer,
+		);
+		assert_ok!(Contracts::add_deployer(Origin::root(), BOB, None));
+
+		// Rejected code is reported as an error so that the upload is paid for.
+		assert!(SudoContracts::upload_code(Origin::signed(BOB), vec![0u8; 8], None).is_err());
+
+		let too_large = vec![0u8; MaxMetadataLen::get() as usize + 1];
+		assert_noop!(
+			SudoContracts::upload_code(Origin::signed(BOB), wasm.clone(), Some(too_large)),
+			pallet_contracts::Error::<Test>::MetadataTooLarge,
+		);
+
+		assert_ok!(SudoContracts::upload_code(Origin::signed(BOB), wasm, Some(vec![1, 2, 3])));
+		assert_eq!(Contracts::code_metadata(&code_hash), Some(vec![1, 2, 3]));
+
+		assert_ok!(SudoContracts::instantiate_with_code(
+			Origin::signed(ALICE), 100, GAS_LIMIT, dummy_wasm.clone(), vec![], vec![],
+			Some(vec![4, 5]),
+		));
+		assert_eq!(last_result(), Ok(()));
+		assert_eq!(Contracts::code_metadata(&dummy_hash), Some(vec![4, 5]));
+
+		// Metadata cannot be replaced by instantiating the same code again.
+		assert_ok!(SudoContracts::instantiate_with_code(
+			Origin::signed(ALICE), 100, GAS_LIMIT, dummy_wasm, vec![], vec![1], Some(vec![6]),
+		));
+		assert_eq!(last_result(), Ok(()));
+		assert_eq!(Contracts::code_metadata(&dummy_hash), Some(vec![4, 5]));
+	});
+}
+
+#[test]
+fn call_requires_signed_origin() {
+	new_test_ext().execute_with(|| {
+		let addr = deploy("dummy");
+
+		assert_noop!(
+			SudoContracts::call(Origin::root(), addr, 0, GAS_LIMIT, vec![]),
+			DispatchError::BadOrigin,
+		);
+	});
+}
+
+#[test]
+fn update_schedule_is_restricted_to_deployers() {
+	new_test_ext().execute_with(|| {
+		let schedule = Schedule::<Test>::default().enable_println(true);
+
+		assert_noop!(
+			SudoContracts::update_schedule(Origin::signed(BOB), schedule.clone()),
+			pallet_contracts::Error::<Test>::NotAuthorizedDeployer,
+		);
+		assert_ok!(SudoContracts::update_schedule(Origin::signed(ALICE), schedule.clone()));
+		assert_eq!(
+			last_event(),
+			Event::pallet_sudo_contracts(SudoContractsEvent::ScheduleUpdated(Some(ALICE))),
+		);
+		assert_ok!(SudoContracts::update_schedule(Origin::root(), schedule));
+		assert_eq!(
+			last_event(),
+			Event::pallet_sudo_contracts(SudoContractsEvent::ScheduleUpdated(None)),
+		);
+	});
+}
+
+#[test]
+fn claim_surcharge_is_restricted_to_sudo() {
+	new_test_ext().execute_with(|| {
+		let addr = deploy("dummy");
+		assert_ok!(Contracts::add_deployer(Origin::root(), BOB, None));
+
+		// Being a deployer does not allow to claim surcharges.
+		assert_noop!(
+			SudoContracts::claim_surcharge(Origin::signed(BOB), addr, None),
+			DispatchError::BadOrigin,
+		);
+	});
+}
+
+#[test]
+fn access_control_is_managed_by_owner() {
+	new_test_ext().execute_with(|| {
+		let addr = deploy("dummy");
+
+		assert_noop!(
+			SudoContracts::grant_access(Origin::signed(BOB), addr.clone(), BOB, None),
+			Error::<Test>::NotContractOwner,
+		);
+		assert_ok!(SudoContracts::grant_access(Origin::signed(ALICE), addr.clone(), BOB, None));
+
+		assert_ok!(SudoContracts::call(Origin::signed(BOB), addr.clone(), 0, GAS_LIMIT, vec![]));
+		assert_noop!(
+			SudoContracts::call(Origin::signed(CHARLIE), addr, 0, GAS_LIMIT, vec![]),
+			Error::<Test>::CallerNotAllowed,
+		);
+	});
+}
+
+#[test]
+fn quota_limits_calls_per_period() {
+	new_test_ext().execute_with(|| {
+		let addr = deploy("dummy");
+		let quota = Quota { max_weight: 10 * GAS_LIMIT, max_calls: 1, period: 10 };
+
+		assert_noop!(
+			SudoContracts::set_account_quota(Origin::signed(ALICE), BOB, Some(quota)),
+			DispatchError::BadOrigin,
+		);
+		assert_noop!(
+			SudoContracts::set_account_quota(Origin::root(), BOB, Some(Quota { period: 0, ..quota })),
+			Error::<Test>::InvalidQuota,
+		);
+		assert_ok!(SudoContracts::set_account_quota(Origin::root(), BOB, Some(quota)));
+		assert_eq!(SudoContracts::remaining_quota(&CHARLIE), None);
+
+		assert_ok!(SudoContracts::call(Origin::signed(BOB), addr.clone(), 0, GAS_LIMIT, vec![]));
+		assert_noop!(
+			SudoContracts::call(Origin::signed(BOB), addr.clone(), 0, GAS_LIMIT, vec![]),
+			Error::<Test>::QuotaExceeded,
+		);
+		assert_eq!(
+			SudoContracts::remaining_quota(&BOB).map(|remaining| (remaining.calls, remaining.resets_at)),
+			Some((0, 11)),
+		);
+
+		System::set_block_number(11);
+		assert_eq!(
+			SudoContracts::remaining_quota(&BOB),
+			Some(RemainingQuota { weight: 10 * GAS_LIMIT, calls: 1, resets_at: 21 }),
+		);
+		assert_ok!(SudoContracts::call(Origin::signed(BOB), addr, 0, GAS_LIMIT, vec![]));
+	});
+}
+
+#[test]
+fn quota_counts_every_batched_call() {
+	new_test_ext().execute_with(|| {
+		let dummy = deploy("dummy");
+		let quota = Quota { max_weight: 10 * GAS_LIMIT, max_calls: 3, period: 10 };
+		assert_ok!(SudoContracts::set_account_quota(Origin::root(), BOB, Some(quota)));
+
+		assert_ok!(SudoContracts::batch_call(
+			Origin::signed(BOB),
+			vec![(dummy.clone(), 0, GAS_LIMIT, vec![]); 2],
+		));
+		assert_eq!(SudoContracts::remaining_quota(&BOB).map(|remaining| remaining.calls), Some(1));
+		assert_noop!(
+			SudoContracts::batch_call(
+				Origin::signed(BOB),
+				vec![(dummy.clone(), 0, GAS_LIMIT, vec![]); 2],
+			),
+			Error::<Test>::QuotaExceeded,
+		);
+		assert_ok!(SudoContracts::call(Origin::signed(BOB), dummy, 0, GAS_LIMIT, vec![]));
+	});
+}
+
+#[test]
+fn check_quota_matches_dispatch() {
+	new_test_ext().execute_with(|| {
+		let addr = deploy("dummy");
+		let (pair, signer) = relay_signer();
+		let quota = Quota { max_weight: GAS_LIMIT, max_calls: 1, period: 10 };
+		assert_ok!(SudoContracts::set_account_quota(Origin::root(), BOB, Some(quota)));
+		assert_ok!(SudoContracts::set_account_quota(Origin::root(), signer.clone(), Some(quota)));
+		let validate = |who: &AccountId32, call: crate::Call<Test>| {
+			let call = Call::SudoContracts(call);
+			CheckQuota::<Test>::new().validate(who, &call, &call.get_dispatch_info(), 0)
+		};
+
+		// Only the gas limit counts, not the weight of the call itself.
+		let call = crate::Call::<Test>::call(addr.clone(), 0, GAS_LIMIT, vec![]);
+		assert!(call.get_dispatch_info().weight > GAS_LIMIT);
+		assert_ok!(validate(&BOB, call));
+		assert_ok!(SudoContracts::call(Origin::signed(BOB), addr.clone(), 0, GAS_LIMIT, vec![]));
+
+		// Relayed calls are checked against the quota of their signer.
+		let relayed = relayed_call(addr, 0);
+		let relay = crate::Call::<Test>::relay_call(
+			signer.clone(), relayed.clone(), sign(&pair, &relayed),
+		);
+		assert_ok!(validate(&BOB, relay.clone()));
+		assert_ok!(SudoContracts::add_relayer(Origin::root(), BOB));
+		assert_ok!(SudoContracts::relay_call(
+			Origin::signed(BOB), signer.clone(), relayed.clone(), sign(&pair, &relayed),
+		));
+		assert_eq!(
+			validate(&CHARLIE, relay).unwrap_err(),
+			InvalidTransaction::Custom(QUOTA_EXCEEDED).into(),
+		);
+	});
+}
+
+#[test]
+fn scheduled_call_runs_once_at_start_block() {
+	new_test_ext().execute_with(|| {
+		let addr = deploy("dummy");
+
+		assert_noop!(
+			SudoContracts::schedule_call(
+				Origin::signed(ALICE), BOB, addr.clone(), GAS_LIMIT, vec![], 3, None, None,
+			),
+			DispatchError::BadOrigin,
+		);
+		assert_ok!(SudoContracts::schedule_call(
+			Origin::root(), BOB, addr.clone(), GAS_LIMIT, vec![], 3, None, None,
+		));
+		assert_eq!(
+			last_event(),
+			Event::pallet_sudo_contracts(SudoContractsEvent::CallScheduled(0, BOB, addr, 3)),
+		);
+
+		run_to_block(2);
+		assert_eq!(scheduled_runs(), vec![]);
+		run_to_block(3);
+		assert_eq!(scheduled_runs(), vec![(0, 1, Ok(()))]);
+		assert_eq!(
+			last_event(),
+			Event::pallet_sudo_contracts(SudoContractsEvent::ScheduledCallCompleted(0)),
+		);
+		assert_eq!(SudoContracts::scheduled_call(0), None);
+	});
+}
+
+#[test]
+fn periodic_call_runs_until_max_runs() {
+	new_test_ext().execute_with(|| {
+		let addr = deploy("dummy");
+
+		assert_ok!(SudoContracts::schedule_call(
+			Origin::root(), BOB, addr.clone(), GAS_LIMIT, vec![], 2, Some(3), Some(2),
+		));
+		assert_ok!(SudoContracts::schedule_call(
+			Origin::root(), BOB, CHARLIE, GAS_LIMIT, vec![], 2, Some(1), None,
+		));
+
+		run_to_block(10);
+		let runs = scheduled_runs();
+		let periodic = runs.iter().filter(|(id, _, _)| *id == 0).collect::<Vec<_>>();
+		assert_eq!(periodic, vec![&(0, 1, Ok(())), &(0, 2, Ok(()))]);
+		assert_eq!(SudoContracts::scheduled_call(0), None);
+
+		let not_callable = Err(pallet_contracts::Error::<Test>::NotCallable.into());
+		assert_eq!(runs.iter().filter(|(id, _, _)| *id == 1).count(), 9);
+		assert!(runs.iter().filter(|(id, _, _)| *id == 1).all(|(_, _, result)| *result == not_callable));
+
+		assert_ok!(SudoContracts::cancel_scheduled_call(Origin::root(), 1));
+		assert_noop!(
+			SudoContracts::cancel_scheduled_call(Origin::root(), 1),
+			Error::<Test>::UnknownScheduledCall,
+		);
+		run_to_block(12);
+		assert_eq!(scheduled_runs().len(), runs.len());
+	});
+}
+
+#[test]
+fn scheduled_calls_over_budget_are_deferred() {
+	new_test_ext().execute_with(|| {
+		let addr = deploy("dummy");
+
+		for _ in 0..3 {
+			assert_ok!(SudoContracts::schedule_call(
+				Origin::root(), BOB, addr.clone(), GAS_LIMIT, vec![], 2, None, None,
+			));
+		}
+		assert_noop!(
+			SudoContracts::schedule_call(
+				Origin::root(), BOB, addr.clone(), GAS_LIMIT, vec![], 2, None, None,
+			),
+			Error::<Test>::AgendaFull,
+		);
+
+		run_to_block(2);
+		assert_eq!(scheduled_runs(), vec![(0, 1, Ok(())), (1, 1, Ok(()))]);
+		assert_eq!(SudoContracts::scheduled_call(2).map(|scheduled| scheduled.next_run), Some(3));
+		run_to_block(3);
+		assert_eq!(scheduled_runs().last(), Some(&(2, 1, Ok(()))));
+	});
+}
+
+#[test]
+fn full_agendas_spill_over_to_later_blocks() {
+	new_test_ext().execute_with(|| {
+		let addr = deploy("dummy");
+		let next_run = |id| SudoContracts::scheduled_call(id).map(|scheduled| scheduled.next_run);
+
+		assert_ok!(SudoContracts::schedule_call(
+			Origin::root(), BOB, addr.clone(), GAS_LIMIT, vec![], 2, Some(1), Some(2),
+		));
+		for start in &[3, 3, 3, 4, 4] {
+			assert_ok!(SudoContracts::schedule_call(
+				Origin::root(), BOB, addr.clone(), GAS_LIMIT, vec![], *start, None, None,
+			));
+		}
+
+		// The periodic call is due at the full block 3 again and moves on to block 4.
+		run_to_block(2);
+		assert_eq!(Agenda::<Test>::get(3), vec![1, 2, 3]);
+		assert_eq!(Agenda::<Test>::get(4), vec![4, 5, 0]);
+		assert_eq!(next_run(0), Some(4));
+
+		// The deferred call goes first and pushes the last call of block 4 out.
+		run_to_block(3);
+		assert_eq!(Agenda::<Test>::get(4), vec![3, 4, 5]);
+		assert_eq!(Agenda::<Test>::get(5), vec![0]);
+		assert_eq!((next_run(3), next_run(0)), (Some(4), Some(5)));
+
+		run_to_block(6);
+		assert_eq!(scheduled_runs().last(), Some(&(0, 2, Ok(()))));
+		assert_eq!(scheduled_runs().len(), 7);
+	});
+}
+
+#[test]
+fn invalid_schedules_are_rejected() {
+	new_test_ext().execute_with(|| {
+		let schedule = |gas_limit, start, period, max_runs| SudoContracts::schedule_call(
+			Origin::root(), BOB, CHARLIE, gas_limit, vec![], start, period, max_runs,
+		);
+
+		assert_noop!(schedule(GAS_LIMIT, 1, None, None), Error::<Test>::ScheduleInPast);
+		assert_noop!(schedule(GAS_LIMIT, 2, Some(0), None), Error::<Test>::InvalidSchedule);
+		assert_noop!(schedule(GAS_LIMIT, 2, Some(1), Some(0)), Error::<Test>::InvalidSchedule);
+		assert_noop!(
+			schedule(3 * GAS_LIMIT, 2, None, None),
+			Error::<Test>::ScheduledGasLimitTooHigh,
+		);
+	});
+}
+
+#[test]
+fn relayed_call_is_made_by_signer() {
+	new_test_ext().execute_with(|| {
+		let addr = deploy("dummy");
+		let (pair, signer) = relay_signer();
+		assert_ok!(SudoContracts::add_relayer(Origin::root(), BOB));
+
+		let call = relayed_call(addr.clone(), 0);
+		let post_info = SudoContracts::relay_call(
+			Origin::signed(BOB), signer.clone(), call.clone(), sign(&pair, &call),
+		).unwrap();
+		assert_eq!(post_info.pays_fee, Pays::No);
+		assert_eq!(last_result(), Ok(()));
+		match last_event() {
+			Event::pallet_sudo_contracts(SudoContractsEvent::ContractCalled(caller, dest, _)) =>
+				assert_eq!((caller, dest), (signer.clone(), addr)),
+			event => panic!("Unexpected event: {:?}", event),
+		}
+		assert!(System::events().iter().any(|record| record.event ==
+			Event::pallet_sudo_contracts(SudoContractsEvent::CallRelayed(BOB, signer.clone(), 0))
+		));
+		assert_eq!(SudoContracts::relay_nonce(&signer), 1);
+		assert_eq!(Balances::free_balance(&BOB), 1_000_000);
+
+		// The same payload cannot be submitted twice.
+		assert_noop!(
+			SudoContracts::relay_call(Origin::signed(BOB), signer, call.clone(), sign(&pair, &call)),
+			Error::<Test>::InvalidRelayNonce,
+		);
+	});
+}
+
+#[test]
+fn invalid_relayed_calls_are_rejected() {
+	new_test_ext().execute_with(|| {
+		let addr = deploy("dummy");
+		let (pair, signer) = relay_signer();
+		let relay = |call: RelayedCall<_, _, _>, signature| SudoContracts::relay_call(
+			Origin::signed(BOB), signer.clone(), call, signature,
+		);
+		let call = relayed_call(addr.clone(), 0);
+
+		assert_noop!(relay(call.clone(), sign(&pair, &call)), Error::<Test>::NotRelayer);
+		assert_ok!(SudoContracts::add_relayer(Origin::root(), BOB));
+
+		let other_chain = RelayedCall { genesis_hash: H256::repeat_byte(1), ..call.clone() };
+		assert_noop!(
+			relay(other_chain.clone(), sign(&pair, &other_chain)),
+			Error::<Test>::WrongGenesisHash,
+		);
+
+		let future_nonce = relayed_call(addr.clone(), 1);
+		assert_noop!(
+			relay(future_nonce.clone(), sign(&pair, &future_nonce)),
+			Error::<Test>::InvalidRelayNonce,
+		);
+
+		let tampered = RelayedCall { data: vec![1], ..call.clone() };
+		assert_noop!(relay(tampered, sign(&pair, &call)), Error::<Test>::InvalidRelaySignature);
+
+		System::set_block_number(11);
+		assert_noop!(relay(call.clone(), sign(&pair, &call)), Error::<Test>::RelayedCallExpired);
+
+		assert_ok!(SudoContracts::remove_relayer(Origin::root(), BOB));
+		assert_noop!(
+			SudoContracts::remove_relayer(Origin::root(), BOB),
+			Error::<Test>::NotRelayer,
+		);
+	});
+}
+
+#[test]
+fn name_references_are_parsed() {
+	assert!(registry::is_valid_name(b"org/payments-v2.1_x"));
+	assert!(!registry::is_valid_name(b"payments"));
+	assert!(!registry::is_valid_name(b"org/"));
+	assert!(!registry::is_valid_name(b"org/team/payments"));
+	assert!(!registry::is_valid_name(b"Org/payments"));
+
+	assert_eq!(registry::parse_reference(b"org/payments"), Some((&b"org/payments"[..], None)));
+	assert_eq!(registry::parse_reference(b"org/payments@v12"), Some((&b"org/payments"[..], Some(12))));
+	assert_eq!(registry::parse_reference(b"org/payments@12"), None);
+	assert_eq!(registry::parse_reference(b"org/payments@v0"), None);
+	assert_eq!(registry::parse_reference(b"org/payments@v"), None);
+	assert_eq!(registry::format_reference(b"org/payments", 120), b"org/payments@v120".to_vec());
+}
+
+#[test]
+fn names_resolve_to_published_versions() {
+	new_test_ext().execute_with(|| {
+		let first = deploy("dummy");
+		let second = deploy("revert_with_data");
+		let name = b"org/payments".to_vec();
+
+		assert_ok!(SudoContracts::register_name(
+			Origin::root(), name.clone(), BOB, first.clone(),
+		));
+		System::set_block_number(2);
+		assert_ok!(SudoContracts::publish_name_version(
+			Origin::signed(BOB), name.clone(), second.clone(),
+		));
+		assert_eq!(
+			last_event(),
+			Event::pallet_sudo_contracts(SudoContractsEvent::NameVersionPublished(
+				name.clone(), 2, second.clone(), compile_module("revert_with_data").1,
+			)),
+		);
+
+		let latest = SudoContracts::resolve(b"org/payments").unwrap();
+		assert_eq!((latest.owner, latest.version, latest.address), (BOB, 2, second.clone()));
+		assert_eq!(latest.published_at, 2);
+		let v1 = SudoContracts::resolve(b"org/payments@v1").unwrap();
+		assert_eq!((v1.address, v1.code_hash), (first.clone(), compile_module("dummy").1));
+		assert_eq!(v1.published_code_hash, v1.code_hash);
+		assert_eq!(SudoContracts::resolve(b"org/payments@v3"), None);
+		assert_eq!(SudoContracts::resolve(b"org/other"), None);
+
+		assert_eq!(SudoContracts::name_of(&first), Some(b"org/payments@v1".to_vec()));
+		assert_eq!(SudoContracts::name_of(&second), Some(b"org/payments@v2".to_vec()));
+		assert_eq!(SudoContracts::name_of(&CHARLIE), None);
+	});
+}
+
+#[test]
+fn names_resolve_to_the_current_code_hash() {
+	let (_, dummy_hash) = compile_module("dummy");
+	let (wasm, code_hash) = compile_module("revert_with_data");
+	new_test_ext().execute_with(|| {
+		let addr = deploy("dummy");
+		assert_ok!(SudoContracts::register_name(
+			Origin::root(), b"org/payments".to_vec(), BOB, addr.clone(),
+		));
+		assert_ok!(SudoContracts::upload_code(Origin::signed(ALICE), wasm, None));
+		assert_ok!(Contracts::approve_code(Origin::root(), code_hash));
+
+		assert_ok!(SudoContracts::set_code(Origin::signed(ALICE), addr.clone(), code_hash));
+		let resolved = SudoContracts::resolve(b"org/payments@v1").unwrap();
+		assert_eq!(resolved.code_hash, code_hash);
+		assert_eq!(resolved.published_code_hash, dummy_hash);
+	});
+}
+
+#[test]
+fn names_are_managed_by_root_or_owner() {
+	new_test_ext().execute_with(|| {
+		let addr = deploy("dummy");
+		let name = b"org/payments".to_vec();
+
+		assert_noop!(
+			SudoContracts::register_name(Origin::signed(BOB), name.clone(), BOB, addr.clone()),
+			DispatchError::BadOrigin,
+		);
+		assert_noop!(
+			SudoContracts::register_name(Origin::root(), b"payments".to_vec(), BOB, addr.clone()),
+			Error::<Test>::InvalidName,
+		);
+		assert_noop!(
+			SudoContracts::register_name(Origin::root(), name.clone(), BOB, CHARLIE),
+			Error::<Test>::NotAContract,
+		);
+		assert_ok!(SudoContracts::register_name(Origin::root(), name.clone(), BOB, addr.clone()));
+		assert_noop!(
+			SudoContracts::register_name(Origin::root(), name.clone(), CHARLIE, addr.clone()),
+			Error::<Test>::NameTaken,
+		);
+
+		assert_noop!(
+			SudoContracts::publish_name_version(Origin::signed(CHARLIE), name.clone(), addr.clone()),
+			Error::<Test>::NotNameOwner,
+		);
 		assert_noop!(
-			TemplateModule::cause_error(Origin::signed(1)),
-			Error::<Test>::NoneValue
+			SudoContracts::transfer_name(Origin::signed(BOB), b"org/other".to_vec(), CHARLIE),
+			Error::<Test>::UnknownName,
 		);
+		assert_ok!(SudoContracts::transfer_name(Origin::signed(BOB), name.clone(), CHARLIE));
+		assert_ok!(SudoContracts::publish_name_version(Origin::signed(CHARLIE), name.clone(), addr));
+		// The sudo key manages every name.
+		assert_ok!(SudoContracts::transfer_name(Origin::signed(ALICE), name, BOB));
 	});
 }
@@ -32,7 +32,7 @@ pub use pallet_balances::Call as BalancesCall;
 pub use sp_runtime::{Permill, Perbill};
 pub use frame_support::{
 	construct_runtime, parameter_types, StorageValue,
-	traits::{KeyOwnerProofSystem, Randomness},
+	traits::{Filter, KeyOwnerProofSystem, Randomness},
 	weights::{
 		Weight, IdentityFee,
 		constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_SECOND},
@@ -45,6 +45,9 @@ use pallet_contracts::weights::WeightInfo;
 /// Import the template pallet.
 pub use pallet_template;
 
+/// Import the sudo smart contracts wrapper pallet.
+pub use rayniel95_pallet_sudo_smart_contracts;
+
 /// An index to a block.
 pub type BlockNumber = u32;
 
@@ -151,11 +154,34 @@ parameter_types! {
 	pub const SS58Prefix: u8 = 42;
 }
 
+/// Rejects the calls of the contracts pallet which skip the checks of the wrapper pallet.
+///
+/// Contracts may only be called, instantiated and uploaded through `SudoContracts` which
+/// enforces access control lists and quotas. The root only calls of the contracts pallet are
+/// still available. Calls dispatched through sudo bypass this filter.
+pub struct BaseFilter;
+
+impl Filter<Call> for BaseFilter {
+	fn filter(call: &Call) -> bool {
+		use pallet_contracts::Call as ContractsCall;
+
+		match call {
+			Call::Contracts(ContractsCall::call(..)) |
+			Call::Contracts(ContractsCall::instantiate_with_code(..)) |
+			Call::Contracts(ContractsCall::instantiate(..)) |
+			Call::Contracts(ContractsCall::upload_code(..)) |
+			Call::Contracts(ContractsCall::update_schedule(..)) |
+			Call::Contracts(ContractsCall::claim_surcharge(..)) => false,
+			_ => true,
+		}
+	}
+}
+
 // Configure FRAME pallets to include in runtime.
 
 impl frame_system::Config for Runtime {
 	/// The basic call filter to use in dispatchable.
-	type BaseCallFilter = ();
+	type BaseCallFilter = BaseFilter;
 	/// Block & extrinsics weights: base values and limits.
 	type BlockWeights = BlockWeights;
 	/// The maximum length of a block (in bytes).
@@ -295,6 +321,105 @@ impl pallet_template::Config for Runtime {
 
 const AVERAGE_ON_INITIALIZE_RATIO: Perbill = Perbill::from_percent(10);
 
+parameter_types! {
+	pub const MaxBatchedCalls: u32 = 16;
+	// Scheduled calls run inside on_initialize like the lazy deletion of contracts.
+	pub MaxScheduledWeight: Weight = AVERAGE_ON_INITIALIZE_RATIO *
+		BlockWeights::get().max_block;
+	pub const MaxScheduledPerBlock: u32 = 50;
+	pub const MaxNameLength: u32 = 64;
+}
+
+/// Configure the contracts wrapper in pallets/rayniel95-pallet-sudo-smart-contracts.
+impl rayniel95_pallet_sudo_smart_contracts::Config for Runtime {
+	type Event = Event;
+	type WeightInfo =
+		rayniel95_pallet_sudo_smart_contracts::placeholder_weights::PlaceholderWeight<Runtime>;
+	type OffchainSignature = Signature;
+	type OffchainPublic = <Signature as Verify>::Signer;
+	type MaxBatchedCalls = MaxBatchedCalls;
+	type MaxScheduledWeight = MaxScheduledWeight;
+	type MaxScheduledPerBlock = MaxScheduledPerBlock;
+	type MaxNameLength = MaxNameLength;
+}
+
+/// Decides which calls are recorded in the audit log.
+///
+/// Only the calls of the sudo key and of deployers are recorded. Node authorization is managed
+/// by the sudo key alone: the calls open to every node owner are not recorded, and the calls
+/// that manage the well known nodes only when they are made with the sudo key.
+pub struct PrivilegedCalls;
+
+impl pallet_audit_log::ClassifyCall<AccountId, Call> for PrivilegedCalls {
+	fn classify(who: &AccountId, call: &Call) -> Option<pallet_audit_log::ActionKind> {
+		let kind = Self::kind(call)?;
+		let privileged = match kind {
+			pallet_audit_log::ActionKind::NodeAuthorization => *who == Sudo::key(),
+			_ => Contracts::is_deployer(who),
+		};
+		if privileged { Some(kind) } else { None }
+	}
+}
+
+impl PrivilegedCalls {
+	fn kind(call: &Call) -> Option<pallet_audit_log::ActionKind> {
+		use pallet_audit_log::ActionKind::*;
+		use pallet_contracts::Call as ContractsCall;
+		use pallet_node_authorization::Call as NodeAuthorizationCall;
+		use pallet_sudo::Call as SudoCall;
+		use rayniel95_pallet_sudo_smart_contracts::Call as SudoContractsCall;
+
+		match call {
+			Call::SudoContracts(call) => match call {
+				SudoContractsCall::call(..) | SudoContractsCall::batch_call(..) |
+				SudoContractsCall::claim_surcharge(..) | SudoContractsCall::relay_call(..) => None,
+				SudoContractsCall::instantiate_with_code(..) | SudoContractsCall::instantiate(..) |
+				SudoContractsCall::upload_code(..) => Some(Deployment),
+				SudoContractsCall::set_code(..) => Some(CodeUpgrade),
+				SudoContractsCall::update_schedule(..) => Some(ScheduleUpdate),
+				_ => Some(ContractAdmin),
+			},
+			Call::Contracts(call) => match call {
+				ContractsCall::call(..) | ContractsCall::claim_surcharge(..) => None,
+				ContractsCall::instantiate_with_code(..) | ContractsCall::instantiate(..) |
+				ContractsCall::upload_code(..) | ContractsCall::approve_code(..) |
+				ContractsCall::reject_code(..) => Some(Deployment),
+				ContractsCall::update_schedule(..) => Some(ScheduleUpdate),
+				_ => Some(ContractAdmin),
+			},
+			Call::NodeAuthorization(call) => match call {
+				NodeAuthorizationCall::add_well_known_node(..) |
+				NodeAuthorizationCall::remove_well_known_node(..) |
+				NodeAuthorizationCall::swap_well_known_node(..) |
+				NodeAuthorizationCall::reset_well_known_nodes(..) => Some(NodeAuthorization),
+				_ => None,
+			},
+			// Calls made with the sudo key are recorded as the action they wrap.
+			Call::Sudo(call) => match call {
+				SudoCall::sudo(call) | SudoCall::sudo_unchecked_weight(call, _) =>
+					Self::kind(call).or(Some(Sudo)),
+				_ => Some(Sudo),
+			},
+			_ => None,
+		}
+	}
+}
+
+parameter_types! {
+	pub const MaxAuditEntries: u64 = 100_000;
+	pub const MaxAuditPageSize: u32 = 100;
+	pub const MaxAuditPageScan: u32 = 10_000;
+}
+
+/// Configure the audit log in pallets/audit-log.
+impl pallet_audit_log::Config for Runtime {
+	type Event = Event;
+	type Classifier = PrivilegedCalls;
+	type MaxEntries = MaxAuditEntries;
+	type MaxPageSize = MaxAuditPageSize;
+	type MaxPageScan = MaxAuditPageScan;
+}
+
 parameter_types! {
 	pub const TombstoneDeposit: Balance = 0;
 	pub const DepositPerContract: Balance = 0;
@@ -304,6 +429,8 @@ parameter_types! {
 	// pub RentFraction: Perbill = 0;
 	// TODO - configure this
 	pub const SurchargeReward: Balance = 0;
+	// Contracts never pay rent and are never evicted.
+	pub const RentFree: bool = true;
 	// TODO - configure this
 	pub const SignedClaimHandicap: u32 = 2;
 	pub const MaxDepth: u32 = 32;
@@ -320,6 +447,8 @@ parameter_types! {
 			<Runtime as pallet_contracts::Config>::WeightInfo::on_initialize_per_queue_item(0)
 		)) / 5) as u32;
 	pub MaxCodeSize: u32 = 128 * 1024;
+	pub MaxMetadataLen: u32 = 128 * 1024;
+	pub MaxDebugBufferLen: u32 = 128 * 1024;
 }
 // TODO - try to configure the parameters here, maybe with () struct
 impl pallet_contracts::Config for Runtime {
@@ -335,14 +464,17 @@ impl pallet_contracts::Config for Runtime {
 	type DepositPerStorageItem = DepositPerStorageItem;
 	type RentFraction = ();
 	type SurchargeReward = SurchargeReward;
+	type RentFree = RentFree;
 	type MaxDepth = MaxDepth;
 	type MaxValueSize = MaxValueSize;
 	type WeightPrice = ();
-	type WeightInfo = ();
+	type WeightInfo = pallet_contracts::weights::SubstrateWeight<Runtime>;
 	type ChainExtension = ();
 	type DeletionQueueDepth = DeletionQueueDepth;
 	type DeletionWeightLimit = DeletionWeightLimit;
 	type MaxCodeSize = MaxCodeSize;
+	type MaxMetadataLen = MaxMetadataLen;
+	type MaxDebugBufferLen = MaxDebugBufferLen;
 }
 
 
@@ -365,6 +497,8 @@ construct_runtime!(
 		TemplateModule: pallet_template::{Pallet, Call, Storage, Event<T>},
 		Contracts: pallet_contracts::{Pallet, Call, Config<T>, Storage, Event<T>},
 		NodeAuthorization: pallet_node_authorization::{Pallet, Call, Storage, Event<T>, Config<T>},
+		SudoContracts: rayniel95_pallet_sudo_smart_contracts::{Pallet, Call, Storage, Event<T>},
+		AuditLog: pallet_audit_log::{Pallet, Storage, Event<T>},
 	}
 );
 
@@ -386,7 +520,9 @@ pub type SignedExtra = (
 	frame_system::CheckEra<Runtime>,
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
-	pallet_transaction_payment::ChargeTransactionPayment<Runtime>
+	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	rayniel95_pallet_sudo_smart_contracts::CheckQuota<Runtime>,
+	pallet_audit_log::RecordPrivileged<Runtime>,
 );
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
@@ -561,6 +697,7 @@ impl_runtime_apis! {
 			add_benchmark!(params, batches, pallet_balances, Balances);
 			add_benchmark!(params, batches, pallet_timestamp, Timestamp);
 			add_benchmark!(params, batches, pallet_template, TemplateModule);
+			add_benchmark!(params, batches, rayniel95_pallet_sudo_smart_contracts, SudoContracts);
 
 			if batches.is_empty() { return Err("Benchmark not found for this pallet.".into()) }
 			Ok(batches)
@@ -578,17 +715,9 @@ impl_runtime_apis! {
 			gas_limit: u64,
 			input_data: Vec<u8>,
 		) -> pallet_contracts_primitives::ContractExecResult {
-			// NOTE - this need to be implemented (mandatory), the idea is to 
-			// create a fixed worng result	
-			pallet_contracts_primitives::ContractExecResult{
-				gas_consumed: 0,
-				debug_message: sp_core::Bytes::from(Vec::<u8>::new()),
-				result: Err(
-					sp_runtime::DispatchError::BadOrigin
-				)
-			}
+			SudoContracts::bare_call(origin, dest, value, gas_limit, input_data)
 		}
-		// TODO - getting error in polkadotjs for this rpc call
+
 		fn instantiate(
 			origin: AccountId,
 			endowment: Balance,
@@ -597,15 +726,7 @@ impl_runtime_apis! {
 			data: Vec<u8>,
 			salt: Vec<u8>,
 		) -> pallet_contracts_primitives::ContractInstantiateResult<AccountId, BlockNumber> {
-			// NOTE - this need to be implemented (mandatory), the idea is to 
-			// create a fixed worng result			
-			pallet_contracts_primitives::ContractInstantiateResult{
-				gas_consumed: 0,
-				debug_message: sp_core::Bytes::from(Vec::<u8>::new()),
-				result: Err(
-					sp_runtime::DispatchError::BadOrigin
-				)
-			}
+			Contracts::bare_instantiate(origin, endowment, gas_limit, code, data, salt, true, true)
 		}
 
 		fn get_storage(
@@ -618,11 +739,137 @@ impl_runtime_apis! {
 		fn rent_projection(
 			address: AccountId,
 		) -> pallet_contracts_primitives::RentProjectionResult<BlockNumber> {
-			// NOTE - this need to be implemented (mandatory), the idea is to 
-			// create a fixed worng result
-			Err(
-				pallet_contracts_primitives::ContractAccessError::DoesntExist
-			)
+			Contracts::rent_projection(address)
+		}
+	}
+
+	impl rayniel95_pallet_sudo_smart_contracts_rpc_runtime_api::SudoContractsApi<Block,
+		AccountId, Balance, BlockNumber, Hash>
+	for Runtime
+	{
+		fn remaining_quota(
+			who: AccountId,
+		) -> Option<rayniel95_pallet_sudo_smart_contracts::RemainingQuota<BlockNumber>> {
+			SudoContracts::remaining_quota(&who)
+		}
+
+		fn is_paused(address: AccountId) -> bool {
+			Contracts::is_paused(&address)
+		}
+
+		fn is_halted() -> bool {
+			Contracts::is_halted()
+		}
+
+		fn relay_nonce(signer: AccountId) -> u64 {
+			SudoContracts::relay_nonce(&signer)
+		}
+
+		fn resolve(
+			name: Vec<u8>,
+		) -> Option<rayniel95_pallet_sudo_smart_contracts::ResolvedName<AccountId, Hash, BlockNumber>> {
+			SudoContracts::resolve(&name)
+		}
+
+		fn name_of(address: AccountId) -> Option<Vec<u8>> {
+			SudoContracts::name_of(&address)
+		}
+
+		fn code_metadata(code_hash: Hash) -> Option<Vec<u8>> {
+			Contracts::code_metadata(&code_hash)
+		}
+
+		fn contract_metadata(address: AccountId) -> Option<Vec<u8>> {
+			Contracts::code_hash(&address).and_then(|code_hash| Contracts::code_metadata(&code_hash))
+		}
+
+		fn storage_usage(address: AccountId) -> Option<pallet_contracts::StorageUsage> {
+			Contracts::storage_usage(&address)
+		}
+
+		fn storage_keys(
+			address: AccountId,
+			prefix: Vec<u8>,
+			start_key: Option<Vec<u8>>,
+			limit: u32,
+		) -> Result<Vec<Vec<u8>>, pallet_contracts_primitives::ContractAccessError> {
+			Contracts::storage_keys(address, prefix, start_key, limit)
+		}
+
+		fn get_storage_many(
+			address: AccountId,
+			keys: Vec<[u8; 32]>,
+		) -> Result<Vec<Option<Vec<u8>>>, pallet_contracts_primitives::ContractAccessError> {
+			Contracts::get_storage_many(address, keys)
+		}
+
+		fn storage_entries(
+			address: AccountId,
+			start_key: Option<Vec<u8>>,
+			limit: u32,
+		) -> Result<Vec<(Vec<u8>, Vec<u8>)>, pallet_contracts_primitives::ContractAccessError> {
+			Contracts::storage_entries(address, start_key, limit)
+		}
+
+		fn code_hash(address: AccountId) -> Option<Hash> {
+			Contracts::code_hash(&address)
+		}
+
+		fn storage_proof_keys(
+			address: AccountId,
+		) -> Result<(Vec<u8>, Vec<u8>), pallet_contracts_primitives::ContractAccessError> {
+			Contracts::storage_proof_keys(address)
+		}
+
+		fn trace_call(
+			origin: AccountId,
+			dest: AccountId,
+			value: Balance,
+			gas_limit: u64,
+			input_data: Vec<u8>,
+		) -> (
+			pallet_contracts_primitives::ContractExecResult,
+			Vec<pallet_contracts::CallFrame<AccountId, Balance, Hash>>,
+		) {
+			SudoContracts::bare_call_traced(origin, dest, value, gas_limit, input_data)
+		}
+
+		fn trace_extrinsic(
+			header: <Block as BlockT>::Header,
+			mut extrinsics: Vec<<Block as BlockT>::Extrinsic>,
+		) -> Option<(
+			ApplyExtrinsicResult,
+			Vec<pallet_contracts::CallFrame<AccountId, Balance, Hash>>,
+			Vec<Vec<u8>>,
+		)> {
+			let target = extrinsics.pop()?;
+			let index = extrinsics.len() as u32;
+			Executive::initialize_block(&header);
+			for extrinsic in extrinsics {
+				// Failed extrinsics are part of the block all the same.
+				let _ = Executive::apply_extrinsic(extrinsic);
+			}
+			let (result, frames) = pallet_contracts::record_frames::<Runtime, _>(||
+				Executive::apply_extrinsic(target)
+			);
+			let events = System::events()
+				.into_iter()
+				.filter(|record| record.phase == frame_system::Phase::ApplyExtrinsic(index))
+				.map(|record| codec::Encode::encode(&record))
+				.collect();
+			Some((result, frames, events))
+		}
+	}
+
+	impl pallet_audit_log_rpc_runtime_api::AuditLogApi<Block, AccountId, BlockNumber, Hash>
+	for Runtime
+	{
+		fn entries(
+			filter: pallet_audit_log::AuditFilter<AccountId>,
+			before: Option<u64>,
+			limit: u32,
+		) -> pallet_audit_log::AuditPage<AccountId, BlockNumber, Hash> {
+			AuditLog::page(filter, before, limit)
 		}
 	}
 }